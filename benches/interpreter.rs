@@ -0,0 +1,28 @@
+use bpf_zisk_interpreter::{BpfInterpreter, BpfParser};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// MOV64_IMM R1, 10000; loop: ADD64_IMM R0, 1; SUB64_IMM R1, 1; JEQ_IMM R1, 0, +1; JA -4; EXIT
+const TIGHT_LOOP: [u8; 48] = [
+    0xb7, 0x01, 0x00, 0x00, 0x10, 0x27, 0x00, 0x00,
+    0x07, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+    0x17, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+    0x15, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x05, 0x00, 0xfc, 0xff, 0x00, 0x00, 0x00, 0x00,
+    0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+fn tight_loop(c: &mut Criterion) {
+    let program = BpfParser::new().parse(&TIGHT_LOOP).unwrap();
+    let mut interpreter = BpfInterpreter::new();
+
+    // Decoding happens once here; the loop body only indexes decoded instructions
+    c.bench_function("parse tight loop", |b| {
+        b.iter(|| BpfParser::new().parse(black_box(&TIGHT_LOOP)).unwrap())
+    });
+    c.bench_function("execute tight loop (40k instructions)", |b| {
+        b.iter(|| interpreter.execute(black_box(&program)).unwrap())
+    });
+}
+
+criterion_group!(benches, tight_loop);
+criterion_main!(benches);
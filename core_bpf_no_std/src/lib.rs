@@ -0,0 +1,8 @@
+//! Compiles the shared `core_bpf` module in a `#![no_std]` crate, the way
+//! the ZisK guest includes it, so a `std` dependency fails this crate's CI
+//! build instead of the guest build.
+
+#![no_std]
+
+#[path = "../../src/core_bpf.rs"]
+pub mod core_bpf;
@@ -0,0 +1,95 @@
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::wire::{write_u32_le, write_u64_le, WireReader};
+
+/// A Solana account as passed into and out of the ZisK input buffer
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolanaAccount {
+    pub pubkey: [u8; 32],
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: [u8; 32],
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+impl SolanaAccount {
+    /// Serialize the account
+    ///
+    /// Layout (integers little-endian): `pubkey: 32 | lamports: u64 |
+    /// data_len: u32 | data | owner: 32 | executable: u8 | rent_epoch: u64`
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(85 + self.data.len());
+        bytes.extend_from_slice(&self.pubkey);
+        write_u64_le(&mut bytes, self.lamports);
+        write_u32_le(&mut bytes, self.data.len() as u32);
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&self.owner);
+        bytes.push(self.executable as u8);
+        write_u64_le(&mut bytes, self.rent_epoch);
+        bytes
+    }
+
+    /// Deserialize an account from the front of `bytes`
+    ///
+    /// Returns the account and the number of bytes consumed, so a stream of
+    /// accounts can be parsed back to back.
+    pub fn deserialize(bytes: &[u8]) -> Result<(Self, usize), TranspilerError> {
+        let mut reader = WireReader::new(bytes, invalid_account);
+        let pubkey = reader.read_array()?;
+        let lamports = reader.read_u64_le()?;
+        let data_len = reader.read_u32_le()? as usize;
+        let data = reader.take(data_len)?.to_vec();
+        let owner = reader.read_array()?;
+        let executable = match reader.take(1)?[0] {
+            0 => false,
+            1 => true,
+            flag => return Err(invalid_account(format!("invalid executable flag {}", flag))),
+        };
+        let rent_epoch = reader.read_u64_le()?;
+
+        let account = Self { pubkey, lamports, data, owner, executable, rent_epoch };
+        Ok((account, reader.offset()))
+    }
+}
+
+fn invalid_account(message: String) -> TranspilerError {
+    TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidAccount { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_stream_round_trip() {
+        let empty = SolanaAccount { pubkey: [1; 32], lamports: 5, owner: [2; 32], ..Default::default() };
+        let large = SolanaAccount {
+            pubkey: [3; 32],
+            lamports: u64::MAX,
+            data: (0..100_000).map(|i| i as u8).collect(),
+            owner: [4; 32],
+            executable: true,
+            rent_epoch: 361,
+        };
+
+        let mut stream = empty.serialize();
+        stream.extend_from_slice(&large.serialize());
+
+        let (first, consumed) = SolanaAccount::deserialize(&stream).unwrap();
+        assert_eq!(first, empty);
+        assert_eq!(consumed, 85);
+        let (second, rest) = SolanaAccount::deserialize(&stream[consumed..]).unwrap();
+        assert_eq!(second, large);
+        assert_eq!(consumed + rest, stream.len());
+    }
+
+    #[test]
+    fn test_malformed_accounts_are_rejected() {
+        let bytes = SolanaAccount::default().serialize();
+        assert!(SolanaAccount::deserialize(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut bad_flag = bytes.clone();
+        bad_flag[76] = 2;
+        assert!(SolanaAccount::deserialize(&bad_flag).is_err());
+    }
+}
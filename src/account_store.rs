@@ -0,0 +1,205 @@
+use crate::account::SolanaAccount;
+use crate::bpf_interpreter::BpfInterpreter;
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::merkle::{AccountLeaf, CommitmentHasher, MerkleTree, Sha256Hasher};
+use crate::program_registry::ProgramRegistry;
+use crate::ExecutionResult;
+use std::collections::HashMap;
+
+/// Default cap on the number of accounts held by a store
+pub const MAX_ACCOUNTS: usize = 64;
+/// Virtual address of the first account mapped for an instruction
+pub const MM_ACCOUNTS_START: u64 = 0x5_0000_0000;
+/// Distance between consecutive mapped accounts
+pub const ACCOUNT_REGION_STRIDE: u64 = 0x1_0000_0000;
+
+/// Virtual address at which the `index`th instruction account is mapped
+pub fn account_address(index: usize) -> u64 {
+    MM_ACCOUNTS_START + index as u64 * ACCOUNT_REGION_STRIDE
+}
+
+/// Accounts keyed by pubkey, committed to by a Merkle state root
+#[derive(Debug, Clone)]
+pub struct AccountStore {
+    accounts: HashMap<[u8; 32], SolanaAccount>,
+    max_accounts: usize,
+}
+
+impl AccountStore {
+    /// Create an empty store holding at most `max_accounts` accounts
+    pub fn new(max_accounts: usize) -> Self {
+        Self {
+            accounts: HashMap::new(),
+            max_accounts,
+        }
+    }
+
+    /// Insert or replace an account, failing if a new key would exceed the cap
+    pub fn insert(&mut self, account: SolanaAccount) -> Result<(), TranspilerError> {
+        if !self.accounts.contains_key(&account.pubkey) && self.accounts.len() >= self.max_accounts {
+            return Err(invalid_account(format!("account limit of {} reached", self.max_accounts)));
+        }
+        self.accounts.insert(account.pubkey, account);
+        Ok(())
+    }
+
+    /// Look up an account
+    pub fn get(&self, pubkey: &[u8; 32]) -> Option<&SolanaAccount> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Number of stored accounts
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether the store holds no accounts
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Merkle root over all accounts' pubkey, lamports and data
+    pub fn state_root(&self) -> [u8; 32] {
+        self.state_root_with::<Sha256Hasher>()
+    }
+
+    /// [`state_root`](Self::state_root) with leaves and nodes hashed by `H`
+    pub fn state_root_with<H: CommitmentHasher>(&self) -> [u8; 32] {
+        let leaves = self
+            .accounts
+            .values()
+            .map(|account| AccountLeaf::new_with::<H>(account.pubkey, account.lamports, &account.data))
+            .collect();
+        MerkleTree::new_with::<H>(leaves).expect("store keys are unique").root()
+    }
+
+    /// Run `program_id` over `accounts` (pubkey, is_writable) in a fresh interpreter
+    ///
+    /// Account data is mapped at [`account_address`] by position, and the
+    /// program may only write accounts it owns. If it exits with 0, writable
+    /// accounts take the data it left behind; otherwise the store is unchanged.
+    pub fn execute_instruction(
+        &mut self,
+        registry: &ProgramRegistry,
+        program_id: &[u8; 32],
+        accounts: &[([u8; 32], bool)],
+    ) -> Result<ExecutionResult, TranspilerError> {
+        let mut interpreter = BpfInterpreter::new();
+        for (index, (pubkey, writable)) in accounts.iter().enumerate() {
+            let account = self
+                .get(pubkey)
+                .ok_or_else(|| invalid_account(format!("unknown account {}", hex(pubkey))))?;
+            interpreter.memory_mut().map_owned_account(
+                account_address(index),
+                account.data.clone(),
+                *writable,
+                Some(account.owner),
+            );
+        }
+
+        let result = registry.execute_instruction(program_id, &mut interpreter)?;
+        if result.exit_code == 0 {
+            for (index, (pubkey, writable)) in accounts.iter().enumerate() {
+                if let (true, Some(data)) = (*writable, interpreter.memory().account_snapshot(account_address(index))) {
+                    self.accounts.get_mut(pubkey).expect("account checked above").data = data;
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Default for AccountStore {
+    fn default() -> Self {
+        Self::new(MAX_ACCOUNTS)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn invalid_account(message: String) -> TranspilerError {
+    TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidAccount { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::BpfProgramBuilder;
+    use crate::error::{MemoryError, MemoryErrorKind};
+
+    const PROGRAM_ID: [u8; 32] = [9; 32];
+
+    fn account(pubkey: u8) -> SolanaAccount {
+        SolanaAccount {
+            pubkey: [pubkey; 32],
+            lamports: 1_000,
+            data: vec![0; 8],
+            owner: PROGRAM_ID,
+            ..SolanaAccount::default()
+        }
+    }
+
+    #[test]
+    fn test_program_writes_reach_state_root() {
+        let program = BpfProgramBuilder::new()
+            .lddw(1, account_address(0))
+            .mov64_imm(2, 0x55)
+            .stx64(1, 2, 0)
+            .mov64_imm(0, 0)
+            .exit()
+            .build();
+        let mut registry = ProgramRegistry::new();
+        registry.load(PROGRAM_ID, program).unwrap();
+
+        let mut store = AccountStore::default();
+        store.insert(account(1)).unwrap();
+        store.insert(account(2)).unwrap();
+        let root_before = store.state_root();
+
+        assert!(store.execute_instruction(&registry, &PROGRAM_ID, &[([1; 32], false)]).is_err());
+        assert_eq!(store.state_root(), root_before);
+
+        store.execute_instruction(&registry, &PROGRAM_ID, &[([1; 32], true)]).unwrap();
+        assert_eq!(store.get(&[1; 32]).unwrap().data, 0x55u64.to_le_bytes());
+        assert_ne!(store.state_root(), root_before);
+    }
+
+    #[test]
+    fn test_writes_to_foreign_accounts_are_denied() {
+        let program = BpfProgramBuilder::new()
+            .lddw(1, account_address(0))
+            .mov64_imm(2, 0x55)
+            .stx64(1, 2, 0)
+            .mov64_imm(0, 0)
+            .exit()
+            .build();
+        let mut registry = ProgramRegistry::new();
+        registry.load(PROGRAM_ID, program).unwrap();
+
+        let mut store = AccountStore::default();
+        store.insert(account(1)).unwrap();
+        store.insert(SolanaAccount { owner: [8; 32], ..account(2) }).unwrap();
+
+        store.execute_instruction(&registry, &PROGRAM_ID, &[([1; 32], true)]).unwrap();
+        assert_eq!(store.get(&[1; 32]).unwrap().data, 0x55u64.to_le_bytes());
+
+        let error = store.execute_instruction(&registry, &PROGRAM_ID, &[([2; 32], true)]).unwrap_err();
+        assert!(matches!(
+            error,
+            TranspilerError::MemoryError(MemoryError { kind: MemoryErrorKind::NotOwner, .. })
+        ));
+        assert_eq!(store.get(&[2; 32]).unwrap().data, vec![0; 8]);
+    }
+
+    #[test]
+    fn test_account_limit_is_enforced() {
+        let mut store = AccountStore::new(2);
+        store.insert(account(1)).unwrap();
+        store.insert(account(2)).unwrap();
+        store.insert(account(2)).unwrap();
+        assert!(store.insert(account(3)).is_err());
+        assert_eq!(store.len(), 2);
+    }
+}
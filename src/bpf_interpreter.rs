@@ -1,13 +1,103 @@
+use crate::core_bpf::{alu32, alu64, alu_overflow, jump_taken, AluFault, OverflowKind};
 use crate::types::{BpfInstruction, BpfOpcode, BpfProgram};
-use crate::error::{InterpreterError, TranspilerError};
-use std::collections::HashMap;
+use crate::error::{BpfExitReason, InterpreterError, TranspilerError};
+use crate::ExecutionResult;
+use crate::memory::{BpfMemory, MemoryConfig};
+use crate::syscalls::{
+    describe_call, CostModel, DefaultSyscalls, LogEntry, LogLevel, ReturnData, SyscallContext, SyscallHandler, MAX_RETURN_DATA,
+};
+use crate::sysvars::Sysvars;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+/// Record a trace line; the message is only formatted when tracing is enabled
+macro_rules! trace {
+    ($interpreter:expr, $($arg:tt)*) => {
+        if $interpreter.tracing {
+            $interpreter.trace.push(format!($($arg)*));
+        }
+    };
+}
+
+/// Number of program counters reported in `ExecutionResult::hottest_pcs`
+const HOTTEST_PC_LIMIT: usize = 10;
+/// Default maximum number of instructions executed before giving up
+pub const DEFAULT_MAX_INSTRUCTIONS: usize = 100_000;
+/// Default compute unit budget (Solana's per-instruction default)
+pub const DEFAULT_COMPUTE_UNITS: u64 = 200_000;
+
+/// Outcome of a bounded `run_steps` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// The program reached `EXIT` (or ran off the end)
+    Halted { exit_code: u64 },
+    /// `max_steps` instructions ran without halting
+    StepLimitReached { remaining_cu: u64 },
+}
+
+/// Outcome of `execute_bounded`
+#[derive(Debug, Clone)]
+pub enum ExecutionOutcome {
+    /// The program reached `EXIT` (or ran off the end)
+    Completed(ExecutionResult),
+    /// The compute budget or instruction limit ran out; the result holds
+    /// the registers and counters at that point
+    Exhausted(ExecutionResult),
+    /// Execution paused before the instruction at a breakpoint
+    BreakpointHit { pc: usize },
+}
+
+/// How the interpreter treats a `CALL` to a syscall no handler recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownSyscallPolicy {
+    /// Fail with `InterpreterError::UnknownSyscall`
+    #[default]
+    Error,
+    /// Charge the base syscall cost, set r0 to 0 and continue
+    NoOp,
+    /// As `NoOp`, also recording a `Debug` log entry
+    LogAndContinue,
+}
+
+/// An executed instruction and the state right after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepRecord {
+    pub pc: usize,
+    pub instruction: BpfInstruction,
+    pub registers: [u64; 11],
+    pub compute_units_used: u64,
+}
 
 /// BPF interpreter that runs natively in ZisK
 pub struct BpfInterpreter {
     registers: [u64; 11],        // BPF registers R0-R10
-    memory: Vec<u8>,             // Memory space for BPF operations
+    entry_registers: [Option<u64>; 11], // Set by the caller, applied at the start of `execute`
+    memory: BpfMemory,           // Stack, heap and mapped regions
     program_counter: usize,      // Current instruction pointer
-    max_memory: usize,           // Maximum memory size
+    instruction_count: usize,    // Jump targets must be below this; set by `step`
+    logs: Vec<LogEntry>,         // Program log output
+    syscall_handler: Option<Box<dyn SyscallHandler>>, // Custom syscalls, consulted first
+    unknown_syscalls: UnknownSyscallPolicy,
+    cost_model: CostModel,       // Compute units charged per syscall
+    sysvars: Sysvars,            // Values returned by sysvar syscalls
+    program_id: [u8; 32],        // Id of the running program, tags return data
+    return_data: ReturnData,
+    max_return_data: usize,      // Cap on sol_set_return_data payloads
+    profiling: bool,             // Collect opcode/pc counts (off for proving runs)
+    tracing: bool,               // Record a line per executed instruction (off for proving runs)
+    tracking_overflow: bool,     // Record wrapping add/sub/mul
+    breakpoints: BTreeSet<usize>, // Program counters where `execute_bounded` pauses
+    overflow_events: Vec<(usize, OverflowKind)>,
+    trace: Vec<String>,
+    recording_steps: bool,       // Keep a StepRecord per executed instruction
+    steps: Vec<StepRecord>,
+    opcode_counts: HashMap<u8, u64>,
+    pc_counts: HashMap<usize, u64>,
+    instructions_executed: usize,
+    max_instructions: usize,     // Hard cap on executed instructions
+    compute_units_limit: u64,
+    compute_units_consumed: u64,
+    exit_code: Option<u64>,      // Set once the program halts
 }
 
 impl BpfInterpreter {
@@ -15,17 +105,214 @@ impl BpfInterpreter {
     pub fn new() -> Self {
         Self {
             registers: [0; 11],
-            memory: vec![0; 1024 * 1024], // 1MB memory
+            entry_registers: [None; 11],
+            memory: BpfMemory::default(),
             program_counter: 0,
-            max_memory: 1024 * 1024,
+            instruction_count: usize::MAX,
+            logs: Vec::new(),
+            syscall_handler: None,
+            unknown_syscalls: UnknownSyscallPolicy::default(),
+            cost_model: CostModel::default(),
+            sysvars: Sysvars::default(),
+            program_id: [0; 32],
+            return_data: ReturnData::default(),
+            max_return_data: MAX_RETURN_DATA,
+            profiling: false,
+            tracing: false,
+            tracking_overflow: false,
+            breakpoints: BTreeSet::new(),
+            overflow_events: Vec::new(),
+            trace: Vec::new(),
+            recording_steps: false,
+            steps: Vec::new(),
+            opcode_counts: HashMap::new(),
+            pc_counts: HashMap::new(),
+            instructions_executed: 0,
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+            compute_units_limit: DEFAULT_COMPUTE_UNITS,
+            compute_units_consumed: 0,
+            exit_code: None,
         }
     }
 
+    /// Create a BPF interpreter with custom heap and stack sizes
+    pub fn with_memory_config(config: MemoryConfig) -> Result<Self, TranspilerError> {
+        config.validate()?;
+        Ok(Self {
+            memory: BpfMemory::from(config),
+            ..Self::new()
+        })
+    }
+
+    /// Create a BPF interpreter with a custom syscall handler
+    ///
+    /// The handler is consulted first; syscalls it does not handle fall back
+    /// to [`DefaultSyscalls`].
+    pub fn with_syscalls(handler: Box<dyn SyscallHandler>) -> Self {
+        Self {
+            syscall_handler: Some(handler),
+            ..Self::new()
+        }
+    }
+
+    /// Set how calls to unrecognized syscalls are handled
+    pub fn set_unknown_syscall_policy(&mut self, policy: UnknownSyscallPolicy) {
+        self.unknown_syscalls = policy;
+    }
+
+    /// Pause `execute_bounded` and `continue_run` before executing the instruction at `pc`
+    pub fn set_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    /// Remove a breakpoint, returning whether it was set
+    pub fn clear_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    /// Set the compute units charged per syscall
+    pub fn set_cost_model(&mut self, cost_model: CostModel) {
+        self.cost_model = cost_model;
+    }
+
+    /// Set the sysvars returned by `sol_get_clock_sysvar`/`sol_get_rent_sysvar`
+    pub fn set_sysvars(&mut self, sysvars: Sysvars) {
+        self.sysvars = sysvars;
+    }
+
+    /// Set the id of the program being executed, reported with its return data
+    /// and checked against the owner of any account it writes
+    pub fn set_program_id(&mut self, program_id: [u8; 32]) {
+        self.program_id = program_id;
+        self.memory.set_program_id(program_id);
+    }
+
+    /// Set the largest payload `sol_set_return_data` accepts (default 1024 bytes)
+    pub fn set_max_return_data(&mut self, max: usize) {
+        self.max_return_data = max;
+    }
+
+    /// Largest payload `sol_set_return_data` accepts
+    pub fn max_return_data(&self) -> usize {
+        self.max_return_data
+    }
+
+    /// Data most recently set by `sol_set_return_data`, here or in a callee
+    pub fn return_data(&self) -> &ReturnData {
+        &self.return_data
+    }
+
     /// Reset interpreter state
+    ///
+    /// Stack and heap are zeroed in place, so their allocations are reused
+    /// across programs; mapped program and account regions are kept.
+    /// The program counter goes back to 0, not to a program's entry point;
+    /// use [`start`](Self::start) to run a program from its entry.
     pub fn reset(&mut self) {
         self.registers = [0; 11];
-        self.memory = vec![0; self.max_memory];
+        self.entry_registers = [None; 11];
+        self.memory.reset();
+        self.registers[10] = self.memory.stack_top();
+        // Per the Solana ABI, r1 points at the serialized input on entry
+        self.registers[1] = self.memory.input_start().unwrap_or(0);
         self.program_counter = 0;
+        self.logs.clear();
+        self.return_data = ReturnData::default();
+        self.trace.clear();
+        self.overflow_events.clear();
+        self.steps.clear();
+        self.opcode_counts.clear();
+        self.pc_counts.clear();
+        self.instructions_executed = 0;
+        self.compute_units_consumed = 0;
+        self.exit_code = None;
+    }
+
+    /// Reset interpreter state and start a fresh compute unit budget
+    pub fn reset_with_budget(&mut self, compute_units_limit: u64) {
+        self.compute_units_limit = compute_units_limit;
+        self.reset();
+    }
+
+    /// Set the hard cap on executed instructions, independent of compute units
+    pub fn set_max_instructions(&mut self, limit: usize) {
+        self.max_instructions = limit;
+    }
+
+    /// Set the compute unit budget
+    pub fn set_compute_units_limit(&mut self, limit: u64) {
+        self.compute_units_limit = limit;
+    }
+
+    /// Compute units left in the budget
+    pub fn compute_units_remaining(&self) -> u64 {
+        self.compute_units_limit.saturating_sub(self.compute_units_consumed)
+    }
+
+    /// Get the current program counter
+    pub fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// Enable or disable opcode and program counter profiling
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    /// Enable or disable recording of wrapping add/sub/mul instructions
+    pub fn set_overflow_tracking(&mut self, enabled: bool) {
+        self.tracking_overflow = enabled;
+    }
+
+    /// Enable or disable per-instruction tracing
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.tracing = enabled;
+    }
+
+    /// Trace lines recorded since the last reset (empty unless tracing is enabled)
+    pub fn trace(&self) -> &[String] {
+        &self.trace
+    }
+
+    /// Enable or disable recording a [`StepRecord`] per executed instruction
+    pub fn set_step_recording(&mut self, enabled: bool) {
+        self.recording_steps = enabled;
+    }
+
+    /// Steps recorded since the last reset (empty unless step recording is enabled)
+    pub fn steps(&self) -> &[StepRecord] {
+        &self.steps
+    }
+
+    /// Write recorded steps as CSV: `pc,opcode,dst,src,imm,r0..r10,cu_used`
+    pub fn write_steps_csv<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let registers: Vec<String> = (0..11).map(|reg| format!("r{}", reg)).collect();
+        writeln!(writer, "pc,opcode,dst,src,imm,{},cu_used", registers.join(","))?;
+        for step in &self.steps {
+            let registers: Vec<String> = step.registers.iter().map(u64::to_string).collect();
+            writeln!(
+                writer,
+                "{},{:#04x},{},{},{},{},{}",
+                step.pc,
+                step.instruction.opcode.code(),
+                step.instruction.dst_reg,
+                step.instruction.src_reg,
+                step.instruction.immediate,
+                registers.join(","),
+                step.compute_units_used
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get program log output
+    pub fn logs(&self) -> &[LogEntry] {
+        &self.logs
+    }
+
+    /// Log messages as the Solana runtime prints them, without levels
+    pub fn logs_as_strings(&self) -> Vec<String> {
+        self.logs.iter().map(|entry| entry.message.clone()).collect()
     }
 
     /// Get current register values
@@ -33,8 +320,23 @@ impl BpfInterpreter {
         self.registers
     }
 
+    /// Current register values
+    pub fn registers(&self) -> &[u64; 11] {
+        &self.registers
+    }
+
     /// Set register value
+    ///
+    /// Values set before `execute` are kept as the program's initial
+    /// registers, overriding the defaults (`r1` = input, `r10` = frame
+    /// pointer) for that run only.
     pub fn set_register(&mut self, reg: u8, value: u64) -> Result<(), TranspilerError> {
+        self.write_register(reg, value)?;
+        self.entry_registers[reg as usize] = Some(value);
+        Ok(())
+    }
+
+    fn write_register(&mut self, reg: u8, value: u64) -> Result<(), TranspilerError> {
         if reg > 10 {
             return Err(TranspilerError::InterpreterError(InterpreterError::InvalidRegister { register: reg }));
         }
@@ -50,334 +352,268 @@ impl BpfInterpreter {
         Ok(self.registers[reg as usize])
     }
 
-    /// Read memory at address
-    pub fn read_memory(&self, address: usize, size: usize) -> Result<&[u8], TranspilerError> {
-        if address + size > self.memory.len() {
-            return Err(TranspilerError::InterpreterError(InterpreterError::MemoryAccessViolation { 
-                address, 
-                size, 
-                max_address: self.memory.len() 
+    /// Read memory at a virtual address
+    pub fn read_memory(&self, address: u64, size: usize) -> Result<&[u8], TranspilerError> {
+        self.memory.read_memory(address, size)
+    }
+
+    /// Write memory at a virtual address
+    pub fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), TranspilerError> {
+        self.memory.write_memory(address, data)
+    }
+
+    /// Get the interpreter's memory for post-execution inspection
+    pub fn memory(&self) -> &BpfMemory {
+        &self.memory
+    }
+
+    /// Get mutable access to memory, e.g. to map accounts before execution
+    pub fn memory_mut(&mut self) -> &mut BpfMemory {
+        &mut self.memory
+    }
+
+    /// Dispatch a syscall to the custom handler, then the built-ins
+    fn invoke_syscall(&mut self, id: u64) -> Result<(), TranspilerError> {
+        let cost = self.cost_model.cost(id);
+        if cost > self.compute_units_remaining() {
+            self.compute_units_consumed = self.compute_units_limit;
+            return Err(TranspilerError::InterpreterError(InterpreterError::ComputeBudgetExceeded {
+                limit: self.compute_units_limit,
             }));
         }
-        Ok(&self.memory[address..address + size])
+        self.compute_units_consumed += cost;
+
+        let mut ctx = SyscallContext {
+            registers: &mut self.registers,
+            memory: &mut self.memory,
+            logs: &mut self.logs,
+            sysvars: &self.sysvars,
+            program_id: &self.program_id,
+            return_data: &mut self.return_data,
+            max_return_data: self.max_return_data,
+        };
+
+        if let Some(handler) = self.syscall_handler.as_mut() {
+            if handler.dispatch(&mut ctx, id)? {
+                return Ok(());
+            }
+        }
+        if DefaultSyscalls.dispatch(&mut ctx, id)? {
+            return Ok(());
+        }
+
+        if self.unknown_syscalls == UnknownSyscallPolicy::LogAndContinue {
+            ctx.log(LogLevel::Debug, format!("Unknown syscall {:#x} ignored", id));
+        }
+        if self.unknown_syscalls == UnknownSyscallPolicy::Error {
+            return Err(TranspilerError::InterpreterError(InterpreterError::UnknownSyscall { id }));
+        }
+        ctx.registers[0] = 0;
+        Ok(())
     }
 
-    /// Write memory at address
-    pub fn write_memory(&mut self, address: usize, data: &[u8]) -> Result<(), TranspilerError> {
-        if address + data.len() > self.memory.len() {
-            return Err(TranspilerError::InterpreterError(InterpreterError::MemoryAccessViolation { 
-                address, 
-                size: data.len(), 
-                max_address: self.memory.len() 
+    /// Jump relative to the next instruction, which must lie inside the program
+    fn jump(&mut self, offset: i16) -> Result<(), TranspilerError> {
+        let target = self.program_counter as i64 + 1 + offset as i64;
+        if target < 0 || target as usize >= self.instruction_count {
+            return Err(TranspilerError::InterpreterError(InterpreterError::InvalidJumpTarget { 
+                target: target as usize 
             }));
         }
-        self.memory[address..address + data.len()].copy_from_slice(data);
+        self.program_counter = target as usize;
         Ok(())
     }
 
+    /// Compute `dst + offset` for memory instructions
+    fn effective_address(&self, instruction: &BpfInstruction) -> Result<u64, TranspilerError> {
+        let base = self.get_register(instruction.dst_reg)?;
+        Ok(base.wrapping_add(instruction.offset as i64 as u64))
+    }
+
+    /// Whether `execute_instruction` implements `opcode`
+    ///
+    /// `CALL` is only supported as a syscall (`src_reg == 0`).
+    pub fn supports_opcode(opcode: BpfOpcode) -> bool {
+        use BpfOpcode::*;
+
+        opcode.is_alu64()
+            || opcode.is_alu32()
+            || opcode.is_jump()
+            || matches!(
+                opcode,
+                LdImm64
+                    | LdAbs8
+                    | LdAbs16
+                    | LdAbs32
+                    | LdAbs64
+                    | Ldx8
+                    | Ldx16
+                    | Ldx32
+                    | Ldx64
+                    | St8
+                    | St16
+                    | St32
+                    | St64
+                    | Stx8
+                    | Stx16
+                    | Stx32
+                    | Stx64
+                    | Call
+                    | Exit
+            )
+    }
+
     /// Execute a single BPF instruction
     pub fn execute_instruction(&mut self, instruction: &BpfInstruction) -> Result<(), TranspilerError> {
         match instruction.opcode {
             // ALU Operations
-            BpfOpcode::Add64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value.wrapping_add(instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Add64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val.wrapping_add(src_val);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Sub64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value.wrapping_sub(instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Sub64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val.wrapping_sub(src_val);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Mul64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value.wrapping_mul(instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Mul64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val.wrapping_mul(src_val);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Div64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let divisor = instruction.immediate as u64;
-                if divisor == 0 {
-                    return Err(TranspilerError::InterpreterError(InterpreterError::DivisionByZero));
+            opcode if opcode.is_alu64() || opcode.is_alu32() => {
+                let operand = if opcode.uses_src_register() {
+                    self.get_register(instruction.src_reg)?
+                } else {
+                    instruction.immediate as u64
+                };
+                let value = self.get_register(instruction.dst_reg)?;
+                if self.tracking_overflow {
+                    if let Some(kind) = alu_overflow(opcode, value, operand) {
+                        self.overflow_events.push((self.program_counter, kind));
+                    }
                 }
-                let result = value / divisor;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Div64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                if src_val == 0 {
-                    return Err(TranspilerError::InterpreterError(InterpreterError::DivisionByZero));
-                }
-                let result = dst_val / src_val;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Mod64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let divisor = instruction.immediate as u64;
-                if divisor == 0 {
-                    return Err(TranspilerError::InterpreterError(InterpreterError::DivisionByZero));
-                }
-                let result = value % divisor;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Mod64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                if src_val == 0 {
-                    return Err(TranspilerError::InterpreterError(InterpreterError::DivisionByZero));
-                }
-                let result = dst_val % src_val;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::And64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value & (instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::And64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val & src_val;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Or64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value | (instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Or64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val | src_val;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Xor64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value ^ (instruction.immediate as u64);
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Xor64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let result = dst_val ^ src_val;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Lsh64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let shift = (instruction.immediate as u64) % 64;
-                let result = value << shift;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Lsh64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let shift = src_val % 64;
-                let result = dst_val << shift;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Rsh64Imm => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let shift = (instruction.immediate as u64) % 64;
-                let result = value >> shift;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Rsh64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                let shift = src_val % 64;
-                let result = dst_val >> shift;
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Neg64 => {
-                let dst = instruction.dst_reg;
-                let value = self.get_register(dst)?;
-                let result = value.wrapping_neg();
-                self.set_register(dst, result)?;
-            }
-            
-            BpfOpcode::Mov64Imm => {
-                let dst = instruction.dst_reg;
-                let value = instruction.immediate as u64;
-                self.set_register(dst, value)?;
-            }
-            
-            BpfOpcode::Mov64Reg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let value = self.get_register(src)?;
-                self.set_register(dst, value)?;
+                let semantics = if opcode.is_alu64() { alu64 } else { alu32 };
+                let result = match semantics(opcode, value, operand) {
+                    Some(Ok(result)) => result,
+                    Some(Err(AluFault::DivisionByZero)) => {
+                        return Err(TranspilerError::InterpreterError(InterpreterError::DivisionByZero));
+                    }
+                    None => unreachable!("ALU opcode without ALU semantics"),
+                };
+                self.write_register(instruction.dst_reg, result)?;
             }
             
             // Memory Operations
             BpfOpcode::LdImm64 => {
                 let dst = instruction.dst_reg;
                 let value = instruction.immediate as u64;
-                self.set_register(dst, value)?;
+                self.write_register(dst, value)?;
             }
             
             BpfOpcode::LdAbs8 => {
                 let dst = instruction.dst_reg;
-                let address = instruction.offset as usize;
+                let address = instruction.offset as u64;
                 let data = self.read_memory(address, 1)?;
                 let value = data[0] as u64;
-                self.set_register(dst, value)?;
+                self.write_register(dst, value)?;
             }
             
             BpfOpcode::LdAbs16 => {
                 let dst = instruction.dst_reg;
-                let address = instruction.offset as usize;
+                let address = instruction.offset as u64;
                 let data = self.read_memory(address, 2)?;
                 let value = u16::from_le_bytes([data[0], data[1]]) as u64;
-                self.set_register(dst, value)?;
+                self.write_register(dst, value)?;
             }
             
             BpfOpcode::LdAbs32 => {
                 let dst = instruction.dst_reg;
-                let address = instruction.offset as usize;
+                let address = instruction.offset as u64;
                 let data = self.read_memory(address, 4)?;
                 let value = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as u64;
-                self.set_register(dst, value)?;
+                self.write_register(dst, value)?;
             }
             
             BpfOpcode::LdAbs64 => {
                 let dst = instruction.dst_reg;
-                let address = instruction.offset as usize;
+                let address = instruction.offset as u64;
                 let data = self.read_memory(address, 8)?;
                 let value = u64::from_le_bytes([
                     data[0], data[1], data[2], data[3],
                     data[4], data[5], data[6], data[7]
                 ]);
-                self.set_register(dst, value)?;
+                self.write_register(dst, value)?;
             }
             
             BpfOpcode::St8 => {
-                let src = instruction.src_reg;
-                let address = instruction.offset as usize;
-                let value = self.get_register(src)? as u8;
+                let address = self.effective_address(instruction)?;
+                let value = (instruction.immediate & 0xff) as u8;
                 self.write_memory(address, &[value])?;
             }
             
             BpfOpcode::St16 => {
-                let src = instruction.src_reg;
-                let address = instruction.offset as usize;
-                let value = self.get_register(src)? as u16;
+                let address = self.effective_address(instruction)?;
+                let value = (instruction.immediate & 0xffff) as u16;
                 let bytes = value.to_le_bytes();
                 self.write_memory(address, &bytes)?;
             }
             
             BpfOpcode::St32 => {
-                let src = instruction.src_reg;
-                let address = instruction.offset as usize;
-                let value = self.get_register(src)? as u32;
+                let address = self.effective_address(instruction)?;
+                let value = (instruction.immediate & 0xffff_ffff) as u32;
                 let bytes = value.to_le_bytes();
                 self.write_memory(address, &bytes)?;
             }
             
             BpfOpcode::St64 => {
-                let src = instruction.src_reg;
-                let address = instruction.offset as usize;
-                let value = self.get_register(src)?;
+                let address = self.effective_address(instruction)?;
+                // The 32-bit immediate is sign-extended to the full doubleword
+                let value = instruction.immediate as i32 as i64 as u64;
                 let bytes = value.to_le_bytes();
                 self.write_memory(address, &bytes)?;
             }
             
+            BpfOpcode::Ldx8 | BpfOpcode::Ldx16 | BpfOpcode::Ldx32 | BpfOpcode::Ldx64 => {
+                let size = match instruction.opcode {
+                    BpfOpcode::Ldx8 => 1,
+                    BpfOpcode::Ldx16 => 2,
+                    BpfOpcode::Ldx32 => 4,
+                    _ => 8,
+                };
+                let base = self.get_register(instruction.src_reg)?;
+                let address = base.wrapping_add(instruction.offset as i64 as u64);
+                // Narrow loads are zero-extended
+                let mut bytes = [0u8; 8];
+                bytes[..size].copy_from_slice(self.read_memory(address, size)?);
+                self.write_register(instruction.dst_reg, u64::from_le_bytes(bytes))?;
+            }
+            
+            BpfOpcode::Stx8 | BpfOpcode::Stx16 | BpfOpcode::Stx32 | BpfOpcode::Stx64 => {
+                let size = match instruction.opcode {
+                    BpfOpcode::Stx8 => 1,
+                    BpfOpcode::Stx16 => 2,
+                    BpfOpcode::Stx32 => 4,
+                    _ => 8,
+                };
+                let address = self.effective_address(instruction)?;
+                let value = self.get_register(instruction.src_reg)?;
+                self.write_memory(address, &value.to_le_bytes()[..size])?;
+            }
+            
             // Branch Operations
             BpfOpcode::Ja => {
-                let offset = instruction.offset as isize;
-                self.program_counter = (self.program_counter as isize + offset) as usize;
-                return Ok(()); // Skip normal PC increment
+                return self.jump(instruction.offset); // Skip normal PC increment
             }
             
-            BpfOpcode::JeqImm => {
-                let dst = instruction.dst_reg;
-                let dst_val = self.get_register(dst)?;
-                let imm = instruction.immediate as u64;
-                if dst_val == imm {
-                    let offset = instruction.offset as isize;
-                    self.program_counter = (self.program_counter as isize + offset) as usize;
-                    return Ok(()); // Skip normal PC increment
+            opcode if opcode.is_jump() => {
+                let operand = if opcode.uses_src_register() {
+                    self.get_register(instruction.src_reg)?
+                } else {
+                    instruction.immediate as u64
+                };
+                let value = self.get_register(instruction.dst_reg)?;
+                if jump_taken(opcode, value, operand).expect("conditional jump without jump semantics") {
+                    return self.jump(instruction.offset); // Skip normal PC increment
                 }
             }
             
-            BpfOpcode::JeqReg => {
-                let dst = instruction.dst_reg;
-                let src = instruction.src_reg;
-                let dst_val = self.get_register(dst)?;
-                let src_val = self.get_register(src)?;
-                if dst_val == src_val {
-                    let offset = instruction.offset as isize;
-                    self.program_counter = (self.program_counter as isize + offset) as usize;
-                    return Ok(()); // Skip normal PC increment
+            BpfOpcode::Call => {
+                // src_reg == 0 marks a syscall; the immediate is the syscall hash
+                if instruction.src_reg != 0 {
+                    return Err(TranspilerError::InterpreterError(InterpreterError::UnsupportedOpcode { 
+                        opcode: instruction.opcode.code(),
+                        index: self.program_counter,
+                        byte_offset: self.program_counter * 8,
+                    }));
                 }
+                self.invoke_syscall(instruction.immediate as u32 as u64)?;
             }
             
             BpfOpcode::Exit => {
@@ -388,7 +624,10 @@ impl BpfInterpreter {
             // Unsupported opcodes
             _ => {
                 return Err(TranspilerError::InterpreterError(InterpreterError::UnsupportedOpcode { 
-                    opcode: instruction.opcode as u8 
+                    opcode: instruction.opcode.code(),
+                    // Exact unless an LD_IMM64 precedes; `step` corrects it from the program
+                    index: self.program_counter,
+                    byte_offset: self.program_counter * 8,
                 }));
             }
         }
@@ -398,34 +637,209 @@ impl BpfInterpreter {
         Ok(())
     }
 
-    /// Execute a complete BPF program
+    /// Execute a complete BPF program, returning the exit code
     pub fn execute_program(&mut self, program: &BpfProgram) -> Result<u64, TranspilerError> {
-        self.reset();
+        self.execute(program).map(|result| result.exit_code)
+    }
+
+    /// Execute the instruction at the current program counter
+    ///
+    /// Returns `Some(exit_code)` once the program has halted.
+    pub fn step(&mut self, program: &BpfProgram) -> Result<Option<u64>, TranspilerError> {
+        if let Some(exit_code) = self.exit_code {
+            return Ok(Some(exit_code));
+        }
         
-        let mut instructions_executed = 0;
-        let start_time = std::time::Instant::now();
+        self.instruction_count = program.instruction_count();
+        let Some(instruction) = program.instruction_at(self.program_counter) else {
+            // Program completed without exit
+            self.exit_code = Some(0);
+            return Ok(self.exit_code);
+        };
         
-        while self.program_counter < program.instructions.len() {
-            let instruction = &program.instructions[self.program_counter];
-            
-            // Handle exit instruction
-            if instruction.opcode == BpfOpcode::Exit {
-                let exit_code = self.get_register(0)?; // R0 contains exit code
-                return Ok(exit_code);
+        // Handle exit instruction
+        if instruction.opcode == BpfOpcode::Exit {
+            self.exit_code = Some(self.get_register(0)?); // R0 contains exit code
+            return Ok(self.exit_code);
+        }
+        
+        // Safety check to prevent infinite loops
+        if self.instructions_executed >= self.max_instructions {
+            return Err(TranspilerError::InterpreterError(InterpreterError::ExecutionLimitExceeded {
+                limit: self.max_instructions,
+            }));
+        }
+        if self.compute_units_remaining() == 0 {
+            return Err(TranspilerError::InterpreterError(InterpreterError::ComputeBudgetExceeded { 
+                limit: self.compute_units_limit 
+            }));
+        }
+        
+        if self.profiling {
+            *self.opcode_counts.entry(instruction.opcode.code()).or_insert(0) += 1;
+            *self.pc_counts.entry(self.program_counter).or_insert(0) += 1;
+        }
+        
+        if instruction.opcode == BpfOpcode::Call {
+            trace!(
+                self,
+                "{:>5}: {}",
+                self.program_counter, describe_call(self.program_counter, instruction.src_reg, instruction.immediate)
+            );
+        } else {
+            trace!(
+                self,
+                "{:>5}: {:?} dst=r{} src=r{} imm={} off={}",
+                self.program_counter, instruction.opcode, instruction.dst_reg,
+                instruction.src_reg, instruction.immediate, instruction.offset
+            );
+        }
+        
+        // Execute instruction
+        let pc = self.program_counter;
+        if let Err(mut error) = self.execute_instruction(instruction) {
+            if let TranspilerError::InterpreterError(InterpreterError::UnsupportedOpcode { byte_offset, .. }) = &mut error {
+                *byte_offset = program.byte_offset(pc);
             }
-            
-            // Execute instruction
-            self.execute_instruction(instruction)?;
-            instructions_executed += 1;
-            
-            // Safety check to prevent infinite loops
-            if instructions_executed > 100_000 {
-                return Err(TranspilerError::InterpreterError(InterpreterError::ExecutionLimitExceeded));
+            return Err(error);
+        }
+        self.instructions_executed += 1;
+        self.compute_units_consumed += 1;
+        if self.recording_steps {
+            self.steps.push(StepRecord {
+                pc,
+                instruction: *instruction,
+                registers: self.registers,
+                compute_units_used: self.compute_units_consumed,
+            });
+        }
+        Ok(None)
+    }
+
+    /// Run at most `max_steps` instructions, resuming from the current
+    /// program counter
+    ///
+    /// Call [`start`](Self::start) before running a new program from its entry point.
+    pub fn run_steps(&mut self, program: &BpfProgram, max_steps: usize) -> Result<RunStatus, TranspilerError> {
+        for _ in 0..max_steps {
+            if let Some(exit_code) = self.step(program)? {
+                return Ok(RunStatus::Halted { exit_code });
+            }
+        }
+        
+        match self.exit_code {
+            Some(exit_code) => Ok(RunStatus::Halted { exit_code }),
+            None => Ok(RunStatus::StepLimitReached { remaining_cu: self.compute_units_remaining() }),
+        }
+    }
+
+    /// Execute a complete BPF program from its entrypoint and collect execution statistics
+    ///
+    /// Every run starts from [`reset`](Self::reset), so stack and heap read
+    /// as zeros however the interpreter was used before. `step` and
+    /// `run_steps` resume the current state instead.
+    pub fn execute(&mut self, program: &BpfProgram) -> Result<ExecutionResult, TranspilerError> {
+        let (exit, start_time) = self.run(program);
+        Ok(self.result(BpfExitReason::Ok(exit?), start_time))
+    }
+
+    /// Execute `program`, reporting program faults in `ExecutionResult::exit_reason` instead of as errors
+    pub fn execute_trapping(&mut self, program: &BpfProgram) -> Result<ExecutionResult, TranspilerError> {
+        let (exit, start_time) = self.run(program);
+        let reason = match exit {
+            Ok(exit_code) => BpfExitReason::Ok(exit_code),
+            Err(error) => BpfExitReason::from_error(&error).ok_or(error)?,
+        };
+        Ok(self.result(reason, start_time))
+    }
+
+    /// Execute `program`, returning the partial state if it runs out of budget
+    ///
+    /// Logs written before the limit stay available through [`logs`](Self::logs).
+    /// Execution pauses at breakpoints; resume with [`continue_run`](Self::continue_run).
+    /// Other faults are still errors.
+    pub fn execute_bounded(&mut self, program: &BpfProgram) -> Result<ExecutionOutcome, TranspilerError> {
+        self.start(program);
+        self.resume(program, false)
+    }
+
+    /// Resume after a breakpoint, until the next breakpoint, exit or exhaustion
+    pub fn continue_run(&mut self, program: &BpfProgram) -> Result<ExecutionOutcome, TranspilerError> {
+        self.resume(program, true)
+    }
+
+    /// Run with breakpoints until the program stops, optionally stepping over
+    /// a breakpoint at the current pc first
+    fn resume(&mut self, program: &BpfProgram, mut step_over: bool) -> Result<ExecutionOutcome, TranspilerError> {
+        let start_time = Instant::now();
+        let exit = loop {
+            let pc = self.program_counter;
+            if !step_over && self.exit_code.is_none() && self.breakpoints.contains(&pc) {
+                return Ok(ExecutionOutcome::BreakpointHit { pc });
+            }
+            step_over = false;
+            match self.step(program) {
+                Ok(Some(exit_code)) => break Ok(exit_code),
+                Ok(None) => {}
+                Err(error) => break Err(error),
+            }
+        };
+        match exit {
+            Ok(exit_code) => Ok(ExecutionOutcome::Completed(self.result(BpfExitReason::Ok(exit_code), start_time))),
+            Err(error) => match BpfExitReason::from_error(&error) {
+                Some(BpfExitReason::ComputeExceeded) => Ok(ExecutionOutcome::Exhausted(
+                    self.result(BpfExitReason::ComputeExceeded, start_time),
+                )),
+                _ => Err(error),
+            },
+        }
+    }
+
+    /// Reset and position at `program`'s entry point, applying register overrides
+    ///
+    /// Call this before driving a new program with [`step`](Self::step) or
+    /// [`run_steps`](Self::run_steps); `execute*` call it themselves.
+    pub fn start(&mut self, program: &BpfProgram) {
+        let entry_registers = self.entry_registers;
+        self.reset();
+        for (register, value) in self.registers.iter_mut().zip(entry_registers) {
+            if let Some(value) = value {
+                *register = value;
             }
         }
+        self.program_counter = program.entry_pc;
+    }
+
+    /// Run `program` from its entry point until it halts or faults, ignoring breakpoints
+    fn run(&mut self, program: &BpfProgram) -> (Result<u64, TranspilerError>, Instant) {
+        self.start(program);
+        let start_time = Instant::now();
+        let exit = loop {
+            match self.step(program) {
+                Ok(Some(exit_code)) => break Ok(exit_code),
+                Ok(None) => {}
+                Err(error) => break Err(error),
+            }
+        };
+        (exit, start_time)
+    }
+
+    fn result(&self, exit_reason: BpfExitReason, start_time: Instant) -> ExecutionResult {
+        let mut hottest_pcs: Vec<(usize, u64)> = self.pc_counts.iter().map(|(pc, count)| (*pc, *count)).collect();
+        hottest_pcs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest_pcs.truncate(HOTTEST_PC_LIMIT);
         
-        // Program completed without exit
-        Ok(0)
+        ExecutionResult {
+            exit_code: exit_reason.code(),
+            exit_reason,
+            registers: self.registers,
+            instructions_executed: self.instructions_executed,
+            logical_time: self.compute_units_consumed,
+            execution_time: start_time.elapsed(),
+            opcode_counts: self.opcode_counts.clone(),
+            hottest_pcs,
+            overflow_events: self.overflow_events.clone(),
+        }
     }
 }
 
@@ -434,3 +848,791 @@ impl Default for BpfInterpreter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpf_parser::{BpfParser, ParseOptions, UnknownPolicy};
+    use crate::memory::{MemoryRegionType, MM_HEAP_START};
+    use crate::syscalls::{LogLevel, SOL_LOG};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct RecordingSyscalls {
+        lines: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl SyscallHandler for RecordingSyscalls {
+        fn dispatch(&mut self, ctx: &mut SyscallContext, id: u64) -> Result<bool, TranspilerError> {
+            if id != SOL_LOG {
+                return Ok(false);
+            }
+            let message = ctx.read_memory(ctx.registers[1], ctx.registers[2])?;
+            self.lines.borrow_mut().push(String::from_utf8_lossy(message).into_owned());
+            Ok(true)
+        }
+    }
+
+    fn instruction(opcode: BpfOpcode, dst_reg: u8, immediate: i64, offset: i16) -> BpfInstruction {
+        BpfInstruction { opcode, dst_reg, src_reg: 0, immediate, offset }
+    }
+
+    #[test]
+    fn test_shift_amounts_are_masked() {
+        let mut interpreter = BpfInterpreter::new();
+
+        interpreter.set_register(1, 0x8000_0000_0000_0001).unwrap();
+        interpreter.execute_instruction(&instruction(BpfOpcode::Lsh64Imm, 1, 64, 0)).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0x8000_0000_0000_0001);
+        interpreter.execute_instruction(&instruction(BpfOpcode::Rsh64Imm, 1, 65, 0)).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0x4000_0000_0000_0000);
+        interpreter.execute_instruction(&instruction(BpfOpcode::Lsh64Imm, 1, 65, 0)).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0x8000_0000_0000_0000);
+        interpreter.execute_instruction(&instruction(BpfOpcode::Arsh64Imm, 1, 64, 0)).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0x8000_0000_0000_0000);
+        interpreter.execute_instruction(&instruction(BpfOpcode::Arsh64Imm, 1, 65, 0)).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0xc000_0000_0000_0000);
+
+        interpreter.set_register(2, 64).unwrap();
+        let shift_by_reg = BpfInstruction { opcode: BpfOpcode::Rsh64Reg, dst_reg: 1, src_reg: 2, immediate: 0, offset: 0 };
+        interpreter.execute_instruction(&shift_by_reg).unwrap();
+        assert_eq!(interpreter.get_register(1).unwrap(), 0xc000_0000_0000_0000);
+    }
+
+    #[test]
+    fn test_store_immediate_widths() {
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_register(1, MM_HEAP_START + 0x100).unwrap();
+
+        interpreter.execute_instruction(&instruction(BpfOpcode::St64, 1, -1, 0)).unwrap();
+        let data = interpreter.read_memory(MM_HEAP_START + 0x100, 8).unwrap();
+        assert_eq!(u64::from_le_bytes(data.try_into().unwrap()), 0xFFFF_FFFF_FFFF_FFFF);
+
+        interpreter.execute_instruction(&instruction(BpfOpcode::St32, 1, 0x1_2345_6789, 8)).unwrap();
+        let data = interpreter.read_memory(MM_HEAP_START + 0x108, 4).unwrap();
+        assert_eq!(u32::from_le_bytes(data.try_into().unwrap()), 0x2345_6789);
+
+        interpreter.execute_instruction(&instruction(BpfOpcode::St16, 1, -2, 12)).unwrap();
+        assert_eq!(interpreter.read_memory(MM_HEAP_START + 0x10c, 2).unwrap(), &[0xfe, 0xff]);
+
+        interpreter.execute_instruction(&instruction(BpfOpcode::St8, 1, 0x1ab, -1)).unwrap();
+        assert_eq!(interpreter.read_memory(MM_HEAP_START + 0xff, 1).unwrap(), &[0xab]);
+    }
+
+    #[test]
+    fn test_register_store_then_load_round_trips() {
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_register(1, MM_HEAP_START + 0x100).unwrap();
+        interpreter.set_register(2, 0x1122_3344_5566_7788).unwrap();
+        let access = |opcode, dst_reg, src_reg, offset| BpfInstruction { opcode, dst_reg, src_reg, immediate: 0, offset };
+
+        for (store, load, expected) in [
+            (BpfOpcode::Stx64, BpfOpcode::Ldx64, 0x1122_3344_5566_7788),
+            (BpfOpcode::Stx32, BpfOpcode::Ldx32, 0x5566_7788),
+            (BpfOpcode::Stx16, BpfOpcode::Ldx16, 0x7788),
+            (BpfOpcode::Stx8, BpfOpcode::Ldx8, 0x88),
+        ] {
+            interpreter.execute_instruction(&access(BpfOpcode::St64, 1, 0, 8)).unwrap();
+            interpreter.execute_instruction(&access(store, 1, 2, 8)).unwrap();
+            interpreter.execute_instruction(&access(load, 3, 1, 8)).unwrap();
+            assert_eq!(interpreter.get_register(3).unwrap(), expected, "{:?}", load);
+        }
+
+        let out_of_bounds = access(BpfOpcode::Ldx64, 3, 1, -0x1000);
+        assert!(interpreter.execute_instruction(&out_of_bounds).is_err());
+    }
+
+    #[test]
+    fn test_supports_opcode_matches_execute_instruction() {
+        for code in 0..=u8::MAX {
+            let Some(opcode) = BpfOpcode::from_code(code) else {
+                continue;
+            };
+            let instruction = BpfInstruction { opcode, dst_reg: 1, src_reg: 0, immediate: 1, offset: 0 };
+            let unsupported = matches!(
+                BpfInterpreter::new().execute_instruction(&instruction),
+                Err(TranspilerError::InterpreterError(InterpreterError::UnsupportedOpcode { .. }))
+            );
+            assert_eq!(BpfInterpreter::supports_opcode(opcode), !unsupported, "{:?}", opcode);
+        }
+    }
+
+    #[test]
+    fn test_registers_set_before_execute_are_honored() {
+        let program = BpfParser::new()
+            .parse(&crate::builder::BpfProgramBuilder::new().ldx64(0, 1, 8).exit().build())
+            .unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.memory_mut().map_input(vec![0; 16]);
+        let mut account = vec![0; 16];
+        account[8..].copy_from_slice(&77u64.to_le_bytes());
+        interpreter.memory_mut().map_account(0x5_0000_0000, account, false);
+
+        interpreter.set_register(1, 0x5_0000_0000).unwrap();
+        assert!(interpreter.set_register(11, 0).is_err());
+        assert_eq!(interpreter.execute(&program).unwrap().exit_code, 77);
+        assert_eq!(interpreter.registers()[1], 0x5_0000_0000);
+
+        // Overrides apply to one run; the next falls back to r1 = input
+        assert_eq!(interpreter.execute(&program).unwrap().exit_code, 0);
+    }
+
+    #[test]
+    fn test_exit_reason_for_division_by_zero() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .mov64_imm(1, 7)
+            .instruction(BpfOpcode::Div64Reg, 1, 2, 0, 0)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        assert!(interpreter.execute(&program).is_err());
+
+        let result = interpreter.execute_trapping(&program).unwrap();
+        assert_eq!(result.exit_reason, BpfExitReason::DivByZero);
+        assert_eq!(result.exit_code, crate::error::ARITHMETIC_OVERFLOW);
+        assert_eq!(result.registers[1], 7);
+    }
+
+    #[test]
+    fn test_exit_reason_for_out_of_bounds_access() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .mov64_imm(0, 3)
+            .lddw(1, 0x5_0000_0000)
+            .ldx64(2, 1, 8)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.memory_mut().map_account(0x5_0000_0000, vec![0; 8], false);
+
+        let result = interpreter.execute_trapping(&program).unwrap();
+        let BpfExitReason::Fault(fault) = &result.exit_reason else {
+            panic!("unexpected exit reason {:?}", result.exit_reason);
+        };
+        assert_eq!(fault.addr, 0x5_0000_0008);
+        assert_eq!(result.exit_code, crate::error::VM_FAULT_BASE + 1);
+        assert_eq!(result.registers[0], 3);
+
+        let in_bounds = crate::builder::BpfProgramBuilder::new().mov64_imm(0, 3).exit().build();
+        let ok = interpreter.execute_trapping(&BpfParser::new().parse(&in_bounds).unwrap()).unwrap();
+        assert_eq!((ok.exit_reason, ok.exit_code), (BpfExitReason::Ok(3), 3));
+    }
+
+    #[test]
+    fn test_overflow_tracking_records_instruction() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .mov64_imm(1, 0x7fff_ffff)
+            .mul64_imm(1, 0x7fff_ffff)
+            .mul64_imm(1, 0x7fff_ffff)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        assert!(interpreter.execute(&program).unwrap().overflow_events.is_empty());
+
+        interpreter.set_overflow_tracking(true);
+        let result = interpreter.execute(&program).unwrap();
+        assert_eq!(result.overflow_events, [(2, OverflowKind::Mul)]);
+    }
+
+    #[test]
+    fn test_alu32_zeroes_upper_bits() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .lddw(0, 0xffff_ffff_0000_0002)
+            .instruction(BpfOpcode::Mul32Imm, 0, 0, 0, 3)
+            .lddw(1, 0x1234_5678_ffff_ffff)
+            .instruction(BpfOpcode::Add32Imm, 1, 0, 0, 2)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let result = BpfInterpreter::new().execute(&program).unwrap();
+        assert_eq!(result.exit_code, 6);
+        assert_eq!(result.registers[1], 1);
+    }
+
+    #[test]
+    fn test_unknown_syscall_policies() {
+        let program = BpfParser::new()
+            .parse(&crate::builder::BpfProgramBuilder::new().mov64_imm(0, 7).syscall(0x1234).exit().build())
+            .unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        let error = interpreter.execute(&program).unwrap_err();
+        assert!(matches!(error, TranspilerError::InterpreterError(InterpreterError::UnknownSyscall { id: 0x1234 })));
+
+        for (policy, log_lines) in [(UnknownSyscallPolicy::NoOp, 0), (UnknownSyscallPolicy::LogAndContinue, 1)] {
+            interpreter.set_unknown_syscall_policy(policy);
+            let result = interpreter.execute(&program).unwrap();
+            assert_eq!(result.exit_code, 0);
+            // MOV and CALL are charged; EXIT halts before charging
+            assert_eq!(result.logical_time, 2 + crate::syscalls::SYSCALL_BASE_COST);
+            assert_eq!(interpreter.logs().len(), log_lines);
+        }
+        assert_eq!(interpreter.logs()[0].level, LogLevel::Debug);
+    }
+
+    #[test]
+    fn test_jset_tests_bits() {
+        // JSET r1, 0x4, +2 skips the `mov r0, 1; exit` fall-through
+        let program = |jset: BpfInstruction| {
+            let mut bytecode = jset.encode().to_vec();
+            bytecode.extend(
+                crate::builder::BpfProgramBuilder::new()
+                    .mov64_imm(0, 1)
+                    .exit()
+                    .mov64_imm(0, 2)
+                    .exit()
+                    .build(),
+            );
+            BpfParser::new().parse(&bytecode).unwrap()
+        };
+        let jset_imm = program(BpfInstruction { opcode: BpfOpcode::JsetImm, dst_reg: 1, src_reg: 0, immediate: 0x4, offset: 2 });
+        let jset_reg = program(BpfInstruction { opcode: BpfOpcode::JsetReg, dst_reg: 1, src_reg: 2, immediate: 0, offset: 2 });
+
+        let mut interpreter = BpfInterpreter::new();
+        for (r1, expected) in [(0b100, 2), (0b1100, 2), (0b011, 1), (0, 1)] {
+            interpreter.set_register(1, r1).unwrap();
+            assert_eq!(interpreter.execute(&jset_imm).unwrap().exit_code, expected, "r1 = {:#b}", r1);
+            interpreter.set_register(1, r1).unwrap();
+            interpreter.set_register(2, 0x4).unwrap();
+            assert_eq!(interpreter.execute(&jset_reg).unwrap().exit_code, expected, "r1 = {:#b}", r1);
+        }
+    }
+
+    #[test]
+    fn test_signed_and_unsigned_jumps_differ_on_high_bit() {
+        // r1 = -1 (u64::MAX), compared with imm -2 or r2; returns 2 if the jump is taken, else 1
+        let program = |opcode| {
+            let mut bytecode = crate::builder::BpfProgramBuilder::new().mov64_imm(1, -1).build();
+            bytecode.extend(BpfInstruction { opcode, dst_reg: 1, src_reg: 2, immediate: -2, offset: 2 }.encode());
+            bytecode.extend(
+                crate::builder::BpfProgramBuilder::new().mov64_imm(0, 1).exit().mov64_imm(0, 2).exit().build(),
+            );
+            BpfParser::new().parse(&bytecode).unwrap()
+        };
+        let mut interpreter = BpfInterpreter::new();
+        let mut run = |opcode, r2: u64| {
+            interpreter.set_register(2, r2).unwrap();
+            interpreter.execute(&program(opcode)).unwrap().exit_code
+        };
+
+        // Unsigned: u64::MAX < 0xffff_ffff_ffff_fffe is false; signed: -1 < -2 is false
+        assert_eq!(run(BpfOpcode::JltImm, 0), 1);
+        assert_eq!(run(BpfOpcode::JsltImm, 0), 1);
+        // Against 1: unsigned u64::MAX < 1 is false, signed -1 < 1 is true
+        assert_eq!(run(BpfOpcode::JltReg, 1), 1);
+        assert_eq!(run(BpfOpcode::JsltReg, 1), 2);
+        assert_eq!(run(BpfOpcode::JgtReg, 1), 2);
+        assert_eq!(run(BpfOpcode::JsgtReg, 1), 1);
+        assert_eq!(run(BpfOpcode::JsgeImm, 0), 2);
+        assert_eq!(run(BpfOpcode::JneImm, 0), 2);
+    }
+
+    #[test]
+    fn test_jset_past_end_is_rejected() {
+        // r1 = r2 = 4, so both forms are taken and land past the final exit
+        let program = |opcode| {
+            let mut bytecode = crate::builder::BpfProgramBuilder::new().mov64_imm(1, 4).mov64_imm(2, 4).build();
+            bytecode.extend(BpfInstruction { opcode, dst_reg: 1, src_reg: 2, immediate: 4, offset: 5 }.encode());
+            bytecode.extend(crate::builder::BpfProgramBuilder::new().exit().build());
+            BpfParser::new().parse(&bytecode).unwrap()
+        };
+        let mut interpreter = BpfInterpreter::new();
+        for opcode in [BpfOpcode::JsetImm, BpfOpcode::JsetReg] {
+            assert!(matches!(
+                interpreter.execute(&program(opcode)),
+                Err(TranspilerError::InterpreterError(InterpreterError::InvalidJumpTarget { target: 8 }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_jump_past_end_is_rejected() {
+        let program = |builder: crate::builder::BpfProgramBuilder| BpfParser::new().parse(&builder.build()).unwrap();
+        let invalid = |result: Result<ExecutionResult, TranspilerError>| {
+            matches!(result, Err(TranspilerError::InterpreterError(InterpreterError::InvalidJumpTarget { .. })))
+        };
+        let mut interpreter = BpfInterpreter::new();
+
+        // mov r0, 0; ja +1; exit: the target is one past the last instruction
+        let ja = program(crate::builder::BpfProgramBuilder::new().mov64_imm(0, 0).ja(1).exit());
+        assert!(invalid(interpreter.execute(&ja)));
+        let jeq = program(crate::builder::BpfProgramBuilder::new().mov64_imm(0, 0).jeq_imm(0, 0, 5).exit());
+        assert!(invalid(interpreter.execute(&jeq)));
+
+        // Not taken, the same branch falls through to exit
+        let untaken = program(crate::builder::BpfProgramBuilder::new().mov64_imm(0, 7).jeq_imm(0, 0, 5).exit());
+        assert_eq!(interpreter.execute(&untaken).unwrap().exit_code, 7);
+    }
+
+    #[test]
+    fn test_reused_interpreter_sees_zeroed_heap_and_stack() {
+        let dirty = crate::builder::BpfProgramBuilder::new()
+            .lddw(1, MM_HEAP_START)
+            .mov64_imm(2, -1)
+            .stx64(1, 2, 0)
+            .stx64(1, 2, 0x100)
+            .stx64(10, 2, -8)
+            .mov64_imm(0, 0)
+            .exit()
+            .build();
+        let read = crate::builder::BpfProgramBuilder::new()
+            .lddw(1, MM_HEAP_START)
+            .ldx64(0, 1, 0)
+            .ldx64(2, 1, 0x100)
+            .add64_reg(0, 2)
+            .ldx64(2, 10, -8)
+            .add64_reg(0, 2)
+            .exit()
+            .build();
+        let parser = BpfParser::new();
+        let (dirty, read) = (parser.parse(&dirty).unwrap(), parser.parse(&read).unwrap());
+
+        let mut interpreter = BpfInterpreter::new();
+        assert_eq!(interpreter.execute(&read).unwrap().exit_code, 0);
+        interpreter.execute(&dirty).unwrap();
+        assert_eq!(interpreter.read_memory(MM_HEAP_START + 0x100, 8).unwrap(), [0xff; 8]);
+        assert_eq!(interpreter.execute(&read).unwrap().exit_code, 0);
+
+        interpreter.memory_mut().alloc(64, 8).unwrap();
+        interpreter.execute(&dirty).unwrap();
+        assert_eq!(interpreter.execute(&read).unwrap().exit_code, 0);
+        assert_eq!(interpreter.memory_mut().alloc(8, 8), Some(MM_HEAP_START));
+    }
+
+    #[test]
+    fn test_bounded_execution_keeps_partial_logs() {
+        // Log "tick" forever: the budget runs out mid-loop
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .lddw(1, 0x5_0000_0000)
+            .mov64_imm(2, 4)
+            .syscall(SOL_LOG)
+            .add64_imm(6, 1)
+            .ja(-4)
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.memory_mut().map_account(0x5_0000_0000, b"tick".to_vec(), false);
+        interpreter.set_compute_units_limit(1_000);
+
+        let ExecutionOutcome::Exhausted(result) = interpreter.execute_bounded(&program).unwrap() else {
+            panic!("program should exhaust its budget");
+        };
+        assert_eq!(result.exit_reason, BpfExitReason::ComputeExceeded);
+        assert_eq!(result.logical_time, 1_000);
+        assert!(result.registers[6] > 0);
+        // The budget runs out charging a CALL, after the last ADD
+        assert_eq!(interpreter.logs().len() as u64, result.registers[6]);
+        assert!(interpreter.logs_as_strings().iter().all(|line| line == "Program log: tick"));
+
+        interpreter.set_compute_units_limit(DEFAULT_COMPUTE_UNITS);
+        let finite = BpfParser::new().parse(&bytecode[..32]).unwrap();
+        assert!(matches!(interpreter.execute_bounded(&finite).unwrap(), ExecutionOutcome::Completed(_)));
+    }
+
+    #[test]
+    fn test_breakpoints_pause_and_resume() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .mov64_imm(0, 1)
+            .mov64_imm(1, 10)
+            .add64_reg(0, 1)
+            .mul64_imm(0, 2)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_breakpoint(2);
+        interpreter.set_breakpoint(3);
+
+        let outcome = interpreter.execute_bounded(&program).unwrap();
+        assert!(matches!(outcome, ExecutionOutcome::BreakpointHit { pc: 2 }));
+        assert_eq!(interpreter.registers()[..2], [1, 10]);
+
+        assert!(matches!(interpreter.continue_run(&program).unwrap(), ExecutionOutcome::BreakpointHit { pc: 3 }));
+        assert_eq!(interpreter.registers()[0], 11);
+
+        let ExecutionOutcome::Completed(result) = interpreter.continue_run(&program).unwrap() else {
+            panic!("program should run to completion");
+        };
+        assert_eq!(result.exit_code, 22);
+        assert_eq!(result.instructions_executed, 4);
+
+        // Plain execution ignores breakpoints
+        assert_eq!(interpreter.execute(&program).unwrap().exit_code, 22);
+        assert!(interpreter.clear_breakpoint(2) && interpreter.clear_breakpoint(3));
+        assert!(matches!(interpreter.execute_bounded(&program).unwrap(), ExecutionOutcome::Completed(_)));
+    }
+
+    #[test]
+    fn test_sha256_charges_configured_cost() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .lddw(1, 0x5_0000_0000)
+            .mov64_imm(2, 0)
+            .mov64_reg(3, 1)
+            .syscall(crate::syscalls::SOL_SHA256)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.memory_mut().map_account(0x5_0000_0000, vec![0; 32], true);
+
+        // LDDW, two MOVs and CALL are one unit each
+        let result = interpreter.execute(&program).unwrap();
+        assert_eq!(result.logical_time, 4 + CostModel::default().sha256);
+
+        interpreter.set_cost_model(CostModel { sha256: 1_000, ..CostModel::default() });
+        assert_eq!(interpreter.execute(&program).unwrap().logical_time, 4 + 1_000);
+
+        interpreter.set_compute_units_limit(500);
+        assert!(matches!(
+            interpreter.execute(&program).unwrap_err(),
+            TranspilerError::InterpreterError(InterpreterError::ComputeBudgetExceeded { limit: 500 })
+        ));
+    }
+
+    #[test]
+    fn test_custom_syscall_handler_records_logs() {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let mut interpreter = BpfInterpreter::with_syscalls(Box::new(RecordingSyscalls { lines: lines.clone() }));
+        let call = BpfInstruction { opcode: BpfOpcode::Call, dst_reg: 0, src_reg: 0, immediate: SOL_LOG as i64, offset: 0 };
+
+        for (address, message) in [(MM_HEAP_START, "first"), (MM_HEAP_START + 0x10, "second")] {
+            interpreter.write_memory(address, message.as_bytes()).unwrap();
+            interpreter.set_register(1, address).unwrap();
+            interpreter.set_register(2, message.len() as u64).unwrap();
+            interpreter.execute_instruction(&call).unwrap();
+        }
+
+        assert_eq!(*lines.borrow(), vec!["first".to_string(), "second".to_string()]);
+        assert!(interpreter.logs().is_empty());
+    }
+
+    #[test]
+    fn test_default_syscalls_handle_sol_log() {
+        let mut interpreter = BpfInterpreter::new();
+        let call = BpfInstruction { opcode: BpfOpcode::Call, dst_reg: 0, src_reg: 0, immediate: SOL_LOG as i64, offset: 0 };
+
+        interpreter.write_memory(MM_HEAP_START, b"hello").unwrap();
+        interpreter.set_register(1, MM_HEAP_START).unwrap();
+        interpreter.set_register(2, 5).unwrap();
+        interpreter.set_program_id([3; 32]);
+        interpreter.execute_instruction(&call).unwrap();
+        assert_eq!(
+            interpreter.logs(),
+            &[LogEntry {
+                level: LogLevel::Info,
+                program_id: Some([3; 32]),
+                message: "Program log: hello".to_string(),
+            }]
+        );
+        assert_eq!(interpreter.logs_as_strings(), ["Program log: hello"]);
+
+        let unknown = BpfInstruction { immediate: 0x1234, ..call };
+        assert!(interpreter.execute_instruction(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_clock_sysvar_is_written_to_caller_buffer() {
+        // MOV64_IMM R1, 3; LSH64_IMM R1, 32; CALL sol_get_clock_sysvar; EXIT
+        let bytecode = vec![
+            0xb7, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x67, 0x01, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+            0x85, 0x00, 0x00, 0x00, 0xe9, 0x5f, 0x6b, 0xd5,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.execute(&program).unwrap();
+        assert_eq!(interpreter.read_memory(MM_HEAP_START, 8).unwrap(), &[0; 8]);
+
+        let mut sysvars = Sysvars::default();
+        sysvars.clock.slot = 123_456;
+        sysvars.clock.unix_timestamp = 1_700_000_000;
+        interpreter.set_sysvars(sysvars);
+        assert_eq!(interpreter.execute(&program).unwrap().exit_code, 0);
+
+        let slot = interpreter.read_memory(MM_HEAP_START, 8).unwrap();
+        assert_eq!(u64::from_le_bytes(slot.try_into().unwrap()), 123_456);
+        let timestamp = interpreter.read_memory(MM_HEAP_START + 32, 8).unwrap();
+        assert_eq!(i64::from_le_bytes(timestamp.try_into().unwrap()), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_heap_dump_after_execution() {
+        // MOV64_IMM R1, 3; LSH64_IMM R1, 32 (R1 = heap start)
+        // ST64 [R1+0], 0x11223344; ST8 [R1+8], 0x55; EXIT
+        let bytecode = vec![
+            0xb7, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x67, 0x01, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+            0x7a, 0x01, 0x00, 0x00, 0x44, 0x33, 0x22, 0x11,
+            0x72, 0x01, 0x08, 0x00, 0x55, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.execute_program(&program).unwrap();
+
+        let heap = interpreter.memory().region_slice(MemoryRegionType::Heap).unwrap();
+        assert_eq!(&heap[..9], &[0x44, 0x33, 0x22, 0x11, 0x00, 0x00, 0x00, 0x00, 0x55]);
+        assert!(heap[9..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_enlarged_heap_accepts_high_writes() {
+        // MOV64_IMM R1, 3; LSH64_IMM R1, 32; ADD64_IMM R1, 0xF000
+        // ST64 [R1+0], 1; EXIT
+        let bytecode = vec![
+            0xb7, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x67, 0x01, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+            0x07, 0x01, 0x00, 0x00, 0x00, 0xf0, 0x00, 0x00,
+            0x7a, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let config = MemoryConfig { heap_size: 64 * 1024, ..MemoryConfig::default() };
+        let mut interpreter = BpfInterpreter::with_memory_config(config).unwrap();
+        assert!(interpreter.execute_program(&program).is_ok());
+        assert_eq!(interpreter.read_memory(MM_HEAP_START + 0xf000, 1).unwrap(), &[1]);
+
+        let mut interpreter = BpfInterpreter::new();
+        assert!(interpreter.execute_program(&program).is_err());
+
+        let invalid = MemoryConfig { stack_size: 1000, ..MemoryConfig::default() };
+        assert!(BpfInterpreter::with_memory_config(invalid).is_err());
+    }
+
+    #[test]
+    fn test_profiling_histogram_is_dominated_by_loop_body() {
+        // MOV64_IMM R1, 5
+        // loop: ADD64_IMM R0, 1; SUB64_IMM R1, 1; JEQ_IMM R1, 0, +1; JA -4
+        // EXIT
+        let bytecode = vec![
+            0xb7, 0x01, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x07, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x17, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x15, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x05, 0x00, 0xfc, 0xff, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_profiling(true);
+        let result = interpreter.execute(&program).unwrap();
+
+        assert_eq!(result.exit_code, 5);
+        assert_eq!(result.instructions_executed, 20);
+        assert_eq!(
+            result.opcode_histogram(),
+            vec![(0x07, 5), (0x15, 5), (0x17, 5), (0x05, 4), (0xb7, 1)]
+        );
+        assert_eq!(&result.hottest_pcs[..3], &[(1, 5), (2, 5), (3, 5)]);
+
+        interpreter.set_profiling(false);
+        let result = interpreter.execute(&program).unwrap();
+        assert!(result.opcode_histogram().is_empty());
+        assert!(result.hottest_pcs.is_empty());
+    }
+
+    #[test]
+    fn test_trace_is_recorded_only_when_enabled() {
+        // MOV64_IMM R0, 1; ADD64_IMM R0, 2; EXIT
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x07, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.execute(&program).unwrap();
+        assert!(interpreter.trace().is_empty());
+
+        interpreter.set_tracing(true);
+        interpreter.execute(&program).unwrap();
+        assert_eq!(
+            interpreter.trace(),
+            &[
+                "    0: Mov64Imm dst=r0 src=r0 imm=1 off=0".to_string(),
+                "    1: Add64Imm dst=r0 src=r0 imm=2 off=0".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_resolves_call_targets() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .lddw(1, 0x5_0000_0000)
+            .mov64_imm(2, 0)
+            .syscall(SOL_LOG)
+            .instruction(BpfOpcode::Call, 0, 1, 0, 2)
+            .exit()
+            .build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.memory_mut().map_account(0x5_0000_0000, Vec::new(), false);
+        interpreter.set_tracing(true);
+
+        // Internal calls are not executed yet, but still traced
+        assert!(interpreter.execute(&program).is_err());
+        assert_eq!(
+            &interpreter.trace()[2..],
+            &["    2: call sol_log_".to_string(), "    3: call function_0x30 (+2)".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_run_steps_resumes_between_pauses() {
+        // MOV64_IMM R0, 1; ADD64_IMM R0, 2; EXIT
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x07, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_compute_units_limit(10);
+        interpreter.reset();
+
+        let status = interpreter.run_steps(&program, 1).unwrap();
+        assert_eq!(status, RunStatus::StepLimitReached { remaining_cu: 9 });
+        assert_eq!(interpreter.get_register(0).unwrap(), 1);
+        assert_eq!(interpreter.program_counter(), 1);
+
+        let status = interpreter.run_steps(&program, 1).unwrap();
+        assert_eq!(status, RunStatus::StepLimitReached { remaining_cu: 8 });
+        assert_eq!(interpreter.get_register(0).unwrap(), 3);
+
+        let status = interpreter.run_steps(&program, 1).unwrap();
+        assert_eq!(status, RunStatus::Halted { exit_code: 3 });
+        assert_eq!(interpreter.run_steps(&program, 5).unwrap(), RunStatus::Halted { exit_code: 3 });
+    }
+
+    #[test]
+    fn test_start_positions_run_steps_at_the_entry_point() {
+        // MOV64_IMM R0, 1; EXIT; MOV64_IMM R0, 2; EXIT
+        let mut program = BpfParser::new().parse(&[
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]).unwrap();
+        program.set_entry_offset(16).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.start(&program);
+        assert_eq!(interpreter.program_counter(), 2);
+        assert_eq!(interpreter.run_steps(&program, 10).unwrap(), RunStatus::Halted { exit_code: 2 });
+    }
+
+    #[test]
+    fn test_reset_reuses_interpreter_across_programs() {
+        // MOV64_IMM R1, 3; LSH64_IMM R1, 32; ST64 [R1+0], 7; MOV64_IMM R0, 1; EXIT
+        let first = BpfParser::new().parse(&[
+            0xb7, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x67, 0x01, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+            0x7a, 0x01, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]).unwrap();
+        // MOV64_IMM R0, 2; EXIT
+        let second = BpfParser::new().parse(&[
+            0xb7, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ]).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.reset_with_budget(100);
+        assert_eq!(interpreter.run_steps(&first, 10).unwrap(), RunStatus::Halted { exit_code: 1 });
+        assert_eq!(interpreter.read_memory(MM_HEAP_START, 8).unwrap(), &7u64.to_le_bytes());
+        assert_eq!(interpreter.compute_units_remaining(), 96);
+
+        interpreter.reset_with_budget(50);
+        assert_eq!(interpreter.read_memory(MM_HEAP_START, 8).unwrap(), &[0; 8]);
+        assert_eq!(interpreter.run_steps(&second, 10).unwrap(), RunStatus::Halted { exit_code: 2 });
+        assert_eq!(interpreter.get_register(1).unwrap(), 0);
+        assert_eq!(interpreter.compute_units_remaining(), 49);
+    }
+
+    #[test]
+    fn test_exit_with_zero_halts_before_trailing_bytes() {
+        // MOV64_IMM R0, 0; EXIT; <garbage>
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xff, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ];
+        let options = ParseOptions { on_unknown: UnknownPolicy::EmitUnknown, ..ParseOptions::default() };
+        let program = BpfParser::new().parse_with_options(&bytecode, &options).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        let result = interpreter.execute(&program).unwrap();
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.instructions_executed, 1);
+        assert_eq!(interpreter.program_counter(), 1);
+    }
+
+    #[test]
+    fn test_unsupported_opcode_error_names_instruction() {
+        // LD_IMM64 R1, 0; MOV64_IMM R0, 0; LD_IND8 R1; EXIT
+        let bytecode = vec![
+            0x18, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x38, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let error = BpfInterpreter::new().execute(&program).unwrap_err();
+        assert!(
+            error.to_string().contains("at BPF instruction #2 (byte 0x18): unsupported opcode 0x38"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_infinite_loop_stops_at_instruction_limit() {
+        // MOV64_IMM R0, 0; loop: JA -1
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x05, 0x00, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_max_instructions(50);
+        let error = interpreter.execute(&program).unwrap_err();
+        assert!(matches!(
+            error,
+            TranspilerError::InterpreterError(InterpreterError::ExecutionLimitExceeded { limit: 50 })
+        ));
+        assert_eq!(interpreter.program_counter(), 1);
+        assert!(interpreter.compute_units_remaining() > 0);
+    }
+
+    #[test]
+    fn test_compute_budget_exhaustion() {
+        // MOV64_IMM R0, 1; ADD64_IMM R0, 2; EXIT
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x07, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let mut interpreter = BpfInterpreter::new();
+        interpreter.set_compute_units_limit(1);
+        assert!(interpreter.execute(&program).is_err());
+
+        interpreter.set_compute_units_limit(2);
+        assert_eq!(interpreter.execute_program(&program).unwrap(), 3);
+    }
+}
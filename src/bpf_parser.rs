@@ -1,8 +1,44 @@
+use crate::core_bpf::decode_slot;
 use crate::types::{BpfInstruction, BpfOpcode, BpfProgram};
-use crate::error::{BpfParseError, TranspilerError};
-use std::collections::HashMap;
+use crate::error::{BpfParseError, TranspilerError, VerifierError};
+use crate::verifier::successors;
+use std::collections::{BTreeSet, HashMap};
+
+/// How the parser treats opcodes it does not recognize
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownPolicy {
+    /// Fail with `BpfParseError::InvalidOpcode`
+    #[default]
+    Error,
+    /// Drop the instruction; jump offsets are not adjusted
+    SkipInstruction,
+    /// Keep the instruction as `BpfOpcode::Unknown`
+    EmitUnknown,
+}
+
+/// Largest program accepted by default (Solana's `MAX_PERMITTED_DATA_LENGTH`, 10 MiB)
+pub const MAX_PROGRAM_BYTES: usize = 10 * 1024 * 1024;
+
+/// Options for [`BpfParser::parse_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub on_unknown: UnknownPolicy,
+    /// Inputs longer than this are rejected before decoding; the parser's
+    /// own `set_max_program_size` limit also applies
+    pub max_program_bytes: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            on_unknown: UnknownPolicy::default(),
+            max_program_bytes: MAX_PROGRAM_BYTES,
+        }
+    }
+}
 
 /// BPF bytecode parser
+#[derive(Debug, Clone)]
 pub struct BpfParser {
     max_program_size: usize,
 }
@@ -17,10 +53,16 @@ impl BpfParser {
     
     /// Parse BPF bytecode into structured instructions
     pub fn parse(&self, bytecode: &[u8]) -> Result<BpfProgram, TranspilerError> {
-        if bytecode.len() > self.max_program_size {
+        self.parse_with_options(bytecode, &ParseOptions::default())
+    }
+    
+    /// Parse BPF bytecode, handling unknown opcodes according to `options`
+    pub fn parse_with_options(&self, bytecode: &[u8], options: &ParseOptions) -> Result<BpfProgram, TranspilerError> {
+        let max_size = self.max_program_size.min(options.max_program_bytes);
+        if bytecode.len() > max_size {
             return Err(TranspilerError::BpfParseError(BpfParseError::ProgramTooLarge { 
                 size: bytecode.len(), 
-                max_size: self.max_program_size 
+                max_size,
             }));
         }
         
@@ -34,7 +76,13 @@ impl BpfParser {
             }
             
             let instruction = self.parse_instruction(bytecode, offset)?;
-            instructions.push(instruction.clone());
+            match (instruction.opcode, options.on_unknown) {
+                (BpfOpcode::Unknown(opcode), UnknownPolicy::Error) => {
+                    return Err(TranspilerError::BpfParseError(BpfParseError::InvalidOpcode { opcode }));
+                }
+                (BpfOpcode::Unknown(_), UnknownPolicy::SkipInstruction) => {}
+                _ => instructions.push(instruction),
+            }
             
             // BPF instructions are 8 bytes, except LD_IMM64 which is 16 bytes
             if instruction.opcode == BpfOpcode::LdImm64 {
@@ -48,9 +96,17 @@ impl BpfParser {
             instructions,
             labels,
             size: bytecode.len(),
+            entry_pc: 0,
         })
     }
     
+    /// Check that registers are written before they are read on every path
+    ///
+    /// See [`crate::verifier::verify_register_init`].
+    pub fn verify_register_init(program: &BpfProgram) -> Result<(), Vec<VerifierError>> {
+        crate::verifier::verify_register_init(program)
+    }
+    
     /// Parse a single BPF instruction
     fn parse_instruction(&self, bytecode: &[u8], offset: usize) -> Result<BpfInstruction, TranspilerError> {
         let opcode = bytecode[offset];
@@ -63,11 +119,14 @@ impl BpfParser {
                 return Err(TranspilerError::BpfParseError(BpfParseError::UnexpectedEndOfInput { offset }));
             }
             
-            let immediate_bytes = &bytecode[offset + 8..offset + 16];
-            let immediate = i64::from_le_bytes([
-                immediate_bytes[0], immediate_bytes[1], immediate_bytes[2], immediate_bytes[3],
-                immediate_bytes[4], immediate_bytes[5], immediate_bytes[6], immediate_bytes[7]
+            // The low word is the first slot's imm, the high word the second slot's imm
+            let low_imm = u32::from_le_bytes([
+                bytecode[offset + 4], bytecode[offset + 5], bytecode[offset + 6], bytecode[offset + 7]
+            ]);
+            let high_imm = u32::from_le_bytes([
+                bytecode[offset + 12], bytecode[offset + 13], bytecode[offset + 14], bytecode[offset + 15]
             ]);
+            let immediate = (((high_imm as u64) << 32) | low_imm as u64) as i64;
 
             Ok(BpfInstruction {
                 opcode: BpfOpcode::LdImm64,
@@ -82,9 +141,6 @@ impl BpfParser {
                 return Err(TranspilerError::BpfParseError(BpfParseError::UnexpectedEndOfInput { offset }));
             }
 
-            let offset_bytes = &bytecode[offset + 2..offset + 4];
-            let immediate_bytes = &bytecode[offset + 4..offset + 8];
-
             // Validate register indices
             if dst_reg > 10 {
                 return Err(TranspilerError::BpfParseError(BpfParseError::InvalidOpcode { opcode: dst_reg }));
@@ -93,97 +149,7 @@ impl BpfParser {
                 return Err(TranspilerError::BpfParseError(BpfParseError::InvalidOpcode { opcode: src_reg }));
             }
 
-            let offset = i16::from_le_bytes([offset_bytes[0], offset_bytes[1]]);
-            let immediate = i64::from_le_bytes([
-                immediate_bytes[0], immediate_bytes[1], immediate_bytes[2], immediate_bytes[3],
-                0, 0, 0, 0
-            ]);
-
-            let opcode = self.parse_opcode(opcode)?;
-
-            Ok(BpfInstruction {
-                opcode,
-                dst_reg,
-                src_reg,
-                immediate,
-                offset,
-            })
-        }
-    }
-    
-    /// Parse BPF opcode
-    fn parse_opcode(&self, opcode: u8) -> Result<BpfOpcode, TranspilerError> {
-        match opcode {
-            0x07 => Ok(BpfOpcode::Add64Imm),
-            0x0f => Ok(BpfOpcode::Add64Reg),
-            0x17 => Ok(BpfOpcode::Sub64Imm),
-            0x1f => Ok(BpfOpcode::Sub64Reg),
-            0x27 => Ok(BpfOpcode::Mul64Imm),
-            0x2f => Ok(BpfOpcode::Mul64Reg),
-            0x37 => Ok(BpfOpcode::Div64Imm),
-            0x3f => Ok(BpfOpcode::Div64Reg),
-            0x47 => Ok(BpfOpcode::Or64Imm),
-            0x4f => Ok(BpfOpcode::Or64Reg),
-            0x57 => Ok(BpfOpcode::And64Imm),
-            0x5f => Ok(BpfOpcode::And64Reg),
-            0x67 => Ok(BpfOpcode::Lsh64Imm),
-            0x6f => Ok(BpfOpcode::Lsh64Reg),
-            0x77 => Ok(BpfOpcode::Rsh64Imm),
-            0x7f => Ok(BpfOpcode::Rsh64Reg),
-            0x87 => Ok(BpfOpcode::Neg64),
-            0x97 => Ok(BpfOpcode::Mod64Imm),
-            0x9f => Ok(BpfOpcode::Mod64Reg),
-            0xa7 => Ok(BpfOpcode::Xor64Imm),
-            0xaf => Ok(BpfOpcode::Xor64Reg),
-            0xb7 => Ok(BpfOpcode::Mov64Imm),
-            0xbf => Ok(BpfOpcode::Mov64Reg),
-            0x18 => Ok(BpfOpcode::LdImm64),
-            0x30 => Ok(BpfOpcode::LdAbs8),
-            0x28 => Ok(BpfOpcode::LdAbs16),
-            0x20 => Ok(BpfOpcode::LdAbs32),
-            0x19 => Ok(BpfOpcode::LdAbs64),
-            0x38 => Ok(BpfOpcode::LdInd8),
-            0x31 => Ok(BpfOpcode::LdInd16),
-            0x29 => Ok(BpfOpcode::LdInd32),
-            0x21 => Ok(BpfOpcode::LdInd64),
-            0x71 => Ok(BpfOpcode::Ldx8),
-            0x69 => Ok(BpfOpcode::Ldx16),
-            0x61 => Ok(BpfOpcode::Ldx32),
-            0x79 => Ok(BpfOpcode::Ldx64),
-            0x72 => Ok(BpfOpcode::St8),
-            0x6a => Ok(BpfOpcode::St16),
-            0x62 => Ok(BpfOpcode::St32),
-            0x7a => Ok(BpfOpcode::St64),
-            0x73 => Ok(BpfOpcode::Stx8),
-            0x6b => Ok(BpfOpcode::Stx16),
-            0x63 => Ok(BpfOpcode::Stx32),
-            0x7b => Ok(BpfOpcode::Stx64),
-            0x05 => Ok(BpfOpcode::Ja),
-            0x15 => Ok(BpfOpcode::JeqImm),
-            0x1d => Ok(BpfOpcode::JeqReg),
-            0x25 => Ok(BpfOpcode::JgtImm),
-            0x2d => Ok(BpfOpcode::JgtReg),
-            0x35 => Ok(BpfOpcode::JgeImm),
-            0x3d => Ok(BpfOpcode::JgeReg),
-            0xa5 => Ok(BpfOpcode::JltImm),
-            0xad => Ok(BpfOpcode::JltReg),
-            0xb5 => Ok(BpfOpcode::JleImm),
-            0xbd => Ok(BpfOpcode::JleReg),
-            0x45 => Ok(BpfOpcode::JsetImm),
-            0x4d => Ok(BpfOpcode::JsetReg),
-            0x55 => Ok(BpfOpcode::JneImm),
-            0x5d => Ok(BpfOpcode::JneReg),
-            0x65 => Ok(BpfOpcode::JsgtImm),
-            0x6d => Ok(BpfOpcode::JsgtReg),
-            0x75 => Ok(BpfOpcode::JsgeImm),
-            0x7d => Ok(BpfOpcode::JsgeReg),
-            0xc5 => Ok(BpfOpcode::JsltImm),
-            0xcd => Ok(BpfOpcode::JsltReg),
-            0xd5 => Ok(BpfOpcode::JsleImm),
-            0xdd => Ok(BpfOpcode::JsleReg),
-            0x85 => Ok(BpfOpcode::Call),
-            0x95 => Ok(BpfOpcode::Exit),
-            _ => Err(TranspilerError::BpfParseError(BpfParseError::InvalidOpcode { opcode })),
+            Ok(decode_slot(bytecode[offset..offset + 8].try_into().unwrap()))
         }
     }
     
@@ -199,6 +165,82 @@ impl Default for BpfParser {
     }
 }
 
+/// Instructions `start..end` (program counters) that always run in sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Control-flow graph of a program, with blocks ordered by start
+#[derive(Debug, Clone, Default)]
+pub struct Cfg {
+    blocks: Vec<BasicBlock>,
+    successors: Vec<Vec<usize>>,
+}
+
+impl Cfg {
+    /// Basic blocks in program order; a block's id is its index here
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Ids of the blocks control may pass to from `block_id`
+    pub fn successors(&self, block_id: usize) -> &[usize] {
+        &self.successors[block_id]
+    }
+
+    /// Total number of edges
+    pub fn edge_count(&self) -> usize {
+        self.successors.iter().map(Vec::len).sum()
+    }
+
+    /// Id of the block containing `pc`
+    pub fn block_of(&self, pc: usize) -> Option<usize> {
+        let index = self.blocks.partition_point(|block| block.start <= pc);
+        index.checked_sub(1).filter(|index| pc < self.blocks[*index].end)
+    }
+}
+
+/// Split `program` into basic blocks at jump targets and after jumps and exits
+///
+/// Edges cover fall-through and taken branches. Targets outside the
+/// program are dropped; `CALL` is treated as falling through.
+pub fn build_cfg(program: &BpfProgram) -> Cfg {
+    let instructions = &program.instructions;
+    let mut leaders = BTreeSet::new();
+    if !instructions.is_empty() {
+        leaders.insert(0);
+        leaders.insert(program.entry_pc);
+    }
+    for (pc, instruction) in instructions.iter().enumerate() {
+        let next = successors(pc, instruction);
+        if next != [pc + 1] {
+            leaders.extend(next.into_iter().chain([pc + 1]).filter(|target| *target < instructions.len()));
+        }
+    }
+
+    let starts: Vec<usize> = leaders.into_iter().collect();
+    let blocks: Vec<BasicBlock> = starts
+        .iter()
+        .zip(starts.iter().skip(1).chain([&instructions.len()]))
+        .map(|(start, end)| BasicBlock { start: *start, end: *end })
+        .collect();
+    let mut cfg = Cfg { blocks, successors: Vec::new() };
+    cfg.successors = cfg
+        .blocks
+        .iter()
+        .map(|block| {
+            let last = block.end - 1;
+            successors(last, &instructions[last])
+                .into_iter()
+                .filter_map(|target| cfg.block_of(target))
+                .collect()
+        })
+        .collect();
+    cfg
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,8 +270,8 @@ mod tests {
         
         // LD_IMM64 R0, 0x1234567890abcdef
         let bytecode = vec![
-            0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0xef, 0xcd, 0xab, 0x90, 0x78, 0x56, 0x34, 0x12,
+            0x18, 0x00, 0x00, 0x00, 0xef, 0xcd, 0xab, 0x90,
+            0x00, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12,
         ];
         
         let result = parser.parse(&bytecode).unwrap();
@@ -241,6 +283,44 @@ mod tests {
         assert_eq!(instruction.immediate, 0x1234567890abcdef);
     }
     
+    #[test]
+    fn test_max_program_bytes_is_enforced() {
+        let mut parser = BpfParser::new();
+        parser.set_max_program_size(usize::MAX);
+        let options = ParseOptions { max_program_bytes: 16, ..ParseOptions::default() };
+
+        let exit = [0x95, 0, 0, 0, 0, 0, 0, 0];
+        let fits = [exit, exit].concat();
+        assert_eq!(parser.parse_with_options(&fits, &options).unwrap().instructions.len(), 2);
+
+        let oversized = [exit, exit, exit].concat();
+        assert!(matches!(
+            parser.parse_with_options(&oversized, &options),
+            Err(TranspilerError::BpfParseError(BpfParseError::ProgramTooLarge { size: 24, max_size: 16 }))
+        ));
+        assert_eq!(ParseOptions::default().max_program_bytes, MAX_PROGRAM_BYTES);
+    }
+
+    #[test]
+    fn test_parse_ld_imm64_word_order() {
+        let parser = BpfParser::new();
+        
+        // LD_IMM64 R3, 0x0102030405060708
+        let bytecode = vec![
+            0x18, 0x03, 0x00, 0x00, 0x08, 0x07, 0x06, 0x05,
+            0x00, 0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+        ];
+        
+        let result = parser.parse(&bytecode).unwrap();
+        let instruction = &result.instructions[0];
+        assert_eq!(instruction.dst_reg, 3);
+        assert_eq!(instruction.immediate as u64, 0x0102030405060708);
+        
+        let mut interpreter = crate::BpfInterpreter::new();
+        interpreter.execute_instruction(instruction).unwrap();
+        assert_eq!(interpreter.get_register(3).unwrap(), 0x0102030405060708);
+    }
+    
     #[test]
     fn test_parse_multiple_instructions() {
         let parser = BpfParser::new();
@@ -262,6 +342,127 @@ mod tests {
         assert_eq!(result.instructions[2].opcode, BpfOpcode::Exit);
     }
     
+    #[test]
+    fn test_parse_negative_immediate_is_sign_extended() {
+        let parser = BpfParser::new();
+        
+        // ADD64_IMM R0, -1
+        // EXIT
+        let bytecode = vec![
+            0x07, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        
+        let result = parser.parse(&bytecode).unwrap();
+        assert_eq!(result.instructions[0].immediate, -1);
+        
+        let mut interpreter = crate::BpfInterpreter::new();
+        let exit_code = interpreter.execute_program(&result).unwrap();
+        assert_eq!(exit_code, 0xFFFF_FFFF_FFFF_FFFF);
+    }
+    
+    #[test]
+    fn test_parse_signed_less_equal_jumps() {
+        let parser = BpfParser::new();
+        
+        // JSLE_IMM R1, 0, +0
+        // JSLE_REG R1, R2, +0
+        let bytecode = vec![
+            0xd5, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xdd, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        
+        let result = parser.parse(&bytecode).unwrap();
+        assert_eq!(result.instructions[0].opcode, BpfOpcode::JsleImm);
+        assert_eq!(result.instructions[1].opcode, BpfOpcode::JsleReg);
+        assert_eq!(BpfOpcode::JsleImm.code(), 0xd5);
+        assert_eq!(BpfOpcode::JsleReg.code(), 0xdd);
+    }
+    
+    #[test]
+    fn test_decoded_instructions_match_on_the_fly_decode() {
+        let parser = BpfParser::new();
+        
+        // MOV64_IMM R1, -3; LD_IMM64 R2, 0x1_0000_0005; ST32 [R10-8], 7; ADD64_REG R1, R2; EXIT
+        let bytecode = vec![
+            0xb7, 0x01, 0x00, 0x00, 0xfd, 0xff, 0xff, 0xff,
+            0x18, 0x02, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x62, 0x0a, 0xf8, 0xff, 0x07, 0x00, 0x00, 0x00,
+            0x0f, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        
+        let program = parser.parse(&bytecode).unwrap();
+        let mut offset = 0;
+        let mut pc = 0;
+        while offset < bytecode.len() {
+            let decoded = parser.parse_instruction(&bytecode, offset).unwrap();
+            assert_eq!(program.instruction_at(pc), Some(&decoded));
+            offset += if decoded.opcode == BpfOpcode::LdImm64 { 16 } else { 8 };
+            pc += 1;
+        }
+        assert_eq!(program.instruction_at(pc), None);
+    }
+    
+    #[test]
+    fn test_unknown_opcode_policies() {
+        let parser = BpfParser::new();
+        
+        // MOV64_IMM R0, 1; <bogus 0xff>; EXIT
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0xff, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let with_policy = |on_unknown| parser.parse_with_options(&bytecode, &ParseOptions { on_unknown, ..ParseOptions::default() });
+        
+        assert!(parser.parse(&bytecode).is_err());
+        assert!(with_policy(UnknownPolicy::Error).is_err());
+        
+        let skipped = with_policy(UnknownPolicy::SkipInstruction).unwrap();
+        let opcodes: Vec<_> = skipped.instructions.iter().map(|i| i.opcode).collect();
+        assert_eq!(opcodes, vec![BpfOpcode::Mov64Imm, BpfOpcode::Exit]);
+        
+        let emitted = with_policy(UnknownPolicy::EmitUnknown).unwrap();
+        assert_eq!(emitted.instructions.len(), 3);
+        assert_eq!(emitted.instructions[1].opcode, BpfOpcode::Unknown(0xff));
+        assert_eq!(emitted.instructions[1].opcode.code(), 0xff);
+        assert_eq!((emitted.instructions[1].dst_reg, emitted.instructions[1].src_reg), (1, 2));
+    }
+    
+    #[test]
+    fn test_size_counts_bytes_and_wide_instructions_once() {
+        let bytecode = crate::builder::BpfProgramBuilder::new().lddw(0, u64::MAX).add64_imm(0, 1).exit().build();
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        assert_eq!(program.size, 32);
+        assert_eq!(program.instruction_count(), 3);
+        assert_eq!(program.byte_offset(program.instruction_count()), program.size);
+    }
+    
+    #[test]
+    fn test_cfg_splits_at_branches_and_loops() {
+        let bytecode = crate::builder::BpfProgramBuilder::new()
+            .mov64_imm(0, 0)
+            .jeq_imm(0, 0, 1) // forward branch over the ADD
+            .add64_imm(0, 1)
+            .mov64_imm(1, 3)
+            .sub64_imm(1, 1)
+            .instruction(BpfOpcode::JneImm, 1, 0, -2, 0) // loop back to the SUB
+            .exit()
+            .build();
+        let cfg = build_cfg(&BpfParser::new().parse(&bytecode).unwrap());
+
+        let starts: Vec<usize> = cfg.blocks().iter().map(|block| block.start).collect();
+        assert_eq!(starts, [0, 2, 3, 4, 6]);
+        assert_eq!(cfg.successors(0), [1, 2]);
+        assert_eq!(cfg.successors(1), [2]);
+        assert_eq!(cfg.successors(3), [4, 3]);
+        assert!(cfg.successors(4).is_empty());
+        assert_eq!(cfg.edge_count(), 6);
+        assert_eq!(cfg.block_of(5), Some(3));
+    }
+    
     #[test]
     fn test_parse_invalid_register() {
         let parser = BpfParser::new();
@@ -0,0 +1,143 @@
+use crate::types::{BpfInstruction, BpfOpcode};
+
+/// Assembles BPF bytecode from fluent instruction helpers,
+/// e.g. `BpfProgramBuilder::new().mov64_imm(0, 42).exit().build()`
+#[derive(Debug, Clone, Default)]
+pub struct BpfProgramBuilder {
+    bytes: Vec<u8>,
+}
+
+impl BpfProgramBuilder {
+    /// Create an empty program
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an arbitrary single-slot instruction
+    pub fn instruction(mut self, opcode: BpfOpcode, dst_reg: u8, src_reg: u8, offset: i16, immediate: i32) -> Self {
+        let instruction = BpfInstruction { opcode, dst_reg, src_reg, immediate: immediate as i64, offset };
+        self.bytes.extend_from_slice(&instruction.encode());
+        self
+    }
+
+    /// `MOV64_IMM dst, imm`
+    pub fn mov64_imm(self, dst: u8, imm: i32) -> Self {
+        self.instruction(BpfOpcode::Mov64Imm, dst, 0, 0, imm)
+    }
+
+    /// `MOV64_REG dst, src`
+    pub fn mov64_reg(self, dst: u8, src: u8) -> Self {
+        self.instruction(BpfOpcode::Mov64Reg, dst, src, 0, 0)
+    }
+
+    /// `ADD64_IMM dst, imm`
+    pub fn add64_imm(self, dst: u8, imm: i32) -> Self {
+        self.instruction(BpfOpcode::Add64Imm, dst, 0, 0, imm)
+    }
+
+    /// `ADD64_REG dst, src`
+    pub fn add64_reg(self, dst: u8, src: u8) -> Self {
+        self.instruction(BpfOpcode::Add64Reg, dst, src, 0, 0)
+    }
+
+    /// `SUB64_IMM dst, imm`
+    pub fn sub64_imm(self, dst: u8, imm: i32) -> Self {
+        self.instruction(BpfOpcode::Sub64Imm, dst, 0, 0, imm)
+    }
+
+    /// `MUL64_IMM dst, imm`
+    pub fn mul64_imm(self, dst: u8, imm: i32) -> Self {
+        self.instruction(BpfOpcode::Mul64Imm, dst, 0, 0, imm)
+    }
+
+    /// `LD_IMM64`: load a full 64-bit constant (two slots)
+    pub fn lddw(mut self, dst: u8, value: u64) -> Self {
+        let instruction = BpfInstruction {
+            opcode: BpfOpcode::LdImm64,
+            dst_reg: dst,
+            src_reg: 0,
+            immediate: value as u32 as i64,
+            offset: 0,
+        };
+        self.bytes.extend_from_slice(&instruction.encode_wide((value >> 32) as u32));
+        self
+    }
+
+    /// `LDX64 dst, [src + offset]`
+    pub fn ldx64(self, dst: u8, src: u8, offset: i16) -> Self {
+        self.instruction(BpfOpcode::Ldx64, dst, src, offset, 0)
+    }
+
+    /// `STX64 [dst + offset], src`
+    pub fn stx64(self, dst: u8, src: u8, offset: i16) -> Self {
+        self.instruction(BpfOpcode::Stx64, dst, src, offset, 0)
+    }
+
+    /// `JA offset`
+    pub fn ja(self, offset: i16) -> Self {
+        self.instruction(BpfOpcode::Ja, 0, 0, offset, 0)
+    }
+
+    /// `JEQ_IMM dst, imm, offset`
+    pub fn jeq_imm(self, dst: u8, imm: i32, offset: i16) -> Self {
+        self.instruction(BpfOpcode::JeqImm, dst, 0, offset, imm)
+    }
+
+    /// `CALL` a syscall by hash (see [`crate::syscalls::syscall_hash`])
+    pub fn syscall(self, id: u64) -> Self {
+        self.instruction(BpfOpcode::Call, 0, 0, 0, id as u32 as i32)
+    }
+
+    /// `EXIT`
+    pub fn exit(self) -> Self {
+        self.instruction(BpfOpcode::Exit, 0, 0, 0, 0)
+    }
+
+    /// Encoded bytecode
+    pub fn build(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpf_parser::BpfParser;
+
+    #[test]
+    fn test_build_encode_decode_round_trip() {
+        let bytecode = BpfProgramBuilder::new()
+            .mov64_imm(1, -7)
+            .lddw(2, 0x1234_5678_9abc_def0)
+            .add64_reg(1, 2)
+            .stx64(10, 1, -8)
+            .jeq_imm(1, 0, 1)
+            .exit()
+            .build();
+        assert_eq!(&bytecode[..8], &[0xb7, 0x01, 0x00, 0x00, 0xf9, 0xff, 0xff, 0xff]);
+
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        let opcodes: Vec<BpfOpcode> = program.instructions.iter().map(|i| i.opcode).collect();
+        assert_eq!(opcodes, vec![
+            BpfOpcode::Mov64Imm,
+            BpfOpcode::LdImm64,
+            BpfOpcode::Add64Reg,
+            BpfOpcode::Stx64,
+            BpfOpcode::JeqImm,
+            BpfOpcode::Exit,
+        ]);
+        assert_eq!(program.instructions[0].immediate, -7);
+        assert_eq!(program.instructions[1].immediate as u64, 0x1234_5678_9abc_def0);
+        assert_eq!((program.instructions[3].dst_reg, program.instructions[3].src_reg, program.instructions[3].offset), (10, 1, -8));
+
+        let mut reencoded = Vec::new();
+        for instruction in &program.instructions {
+            if instruction.opcode == BpfOpcode::LdImm64 {
+                reencoded.extend_from_slice(&instruction.encode_wide((instruction.immediate as u64 >> 32) as u32));
+            } else {
+                reencoded.extend_from_slice(&instruction.encode());
+            }
+        }
+        assert_eq!(reencoded, bytecode);
+    }
+}
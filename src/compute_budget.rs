@@ -0,0 +1,190 @@
+use crate::bpf_interpreter::{BpfInterpreter, DEFAULT_COMPUTE_UNITS};
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::memory::{MemoryConfig, DEFAULT_HEAP_SIZE, MAX_HEAP_SIZE};
+
+/// `ComputeBudget111111111111111111111111111111`
+pub const COMPUTE_BUDGET_PROGRAM_ID: [u8; 32] = [
+    3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231, 188, 140, 229, 187,
+    197, 247, 18, 107, 44, 67, 155, 58, 64, 0, 0, 0,
+];
+
+/// Largest compute unit limit a transaction may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Requested heap frames must be a multiple of this size
+const HEAP_FRAME_GRANULARITY: u32 = 1024;
+
+/// A decoded ComputeBudget program instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBudgetInstruction {
+    RequestHeapFrame(u32),
+    SetComputeUnitLimit(u32),
+    SetComputeUnitPrice(u64),
+}
+
+impl ComputeBudgetInstruction {
+    /// Decode borsh-encoded instruction data (`[tag: u8][value LE]`)
+    pub fn parse(data: &[u8]) -> Result<Self, TranspilerError> {
+        let (tag, payload) = data
+            .split_first()
+            .ok_or_else(|| invalid_budget("empty instruction data".to_string()))?;
+
+        match (tag, payload.len()) {
+            (1, 4) => Ok(Self::RequestHeapFrame(u32::from_le_bytes(payload.try_into().unwrap()))),
+            (2, 4) => Ok(Self::SetComputeUnitLimit(u32::from_le_bytes(payload.try_into().unwrap()))),
+            (3, 8) => Ok(Self::SetComputeUnitPrice(u64::from_le_bytes(payload.try_into().unwrap()))),
+            _ => Err(invalid_budget(format!(
+                "unsupported instruction tag {} with {} payload bytes",
+                tag,
+                payload.len()
+            ))),
+        }
+    }
+}
+
+/// Effective execution limits for a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub compute_unit_limit: u64,
+    pub compute_unit_price: u64,
+    pub heap_size: usize,
+}
+
+impl ComputeBudget {
+    /// Scan `(program_id, data)` pairs and apply any ComputeBudget instructions
+    ///
+    /// Instructions for other programs are ignored. Each ComputeBudget
+    /// instruction kind may appear at most once.
+    pub fn from_instructions<'a, I>(instructions: I) -> Result<Self, TranspilerError>
+    where
+        I: IntoIterator<Item = (&'a [u8; 32], &'a [u8])>,
+    {
+        let mut heap_frame = None;
+        let mut unit_limit = None;
+        let mut unit_price = None;
+
+        for (program_id, data) in instructions {
+            if *program_id != COMPUTE_BUDGET_PROGRAM_ID {
+                continue;
+            }
+
+            let instruction = ComputeBudgetInstruction::parse(data)?;
+            let duplicate = match instruction {
+                ComputeBudgetInstruction::RequestHeapFrame(bytes) => heap_frame.replace(bytes).is_some(),
+                ComputeBudgetInstruction::SetComputeUnitLimit(units) => unit_limit.replace(units).is_some(),
+                ComputeBudgetInstruction::SetComputeUnitPrice(price) => unit_price.replace(price).is_some(),
+            };
+            if duplicate {
+                return Err(invalid_budget(format!("duplicate instruction {:?}", instruction)));
+            }
+        }
+
+        let heap_size = match heap_frame {
+            Some(bytes) => {
+                if !bytes.is_multiple_of(HEAP_FRAME_GRANULARITY)
+                    || !(DEFAULT_HEAP_SIZE..=MAX_HEAP_SIZE).contains(&(bytes as usize))
+                {
+                    return Err(invalid_budget(format!("invalid heap frame size {}", bytes)));
+                }
+                bytes as usize
+            }
+            None => DEFAULT_HEAP_SIZE,
+        };
+
+        Ok(Self {
+            compute_unit_limit: unit_limit.map_or(DEFAULT_COMPUTE_UNITS, |units| {
+                units.min(MAX_COMPUTE_UNIT_LIMIT) as u64
+            }),
+            compute_unit_price: unit_price.unwrap_or(0),
+            heap_size,
+        })
+    }
+
+    /// Memory configuration with the requested heap size
+    pub fn memory_config(&self) -> MemoryConfig {
+        MemoryConfig {
+            heap_size: self.heap_size,
+            ..MemoryConfig::default()
+        }
+    }
+
+    /// Create an interpreter with these limits applied
+    pub fn build_interpreter(&self) -> Result<BpfInterpreter, TranspilerError> {
+        let mut interpreter = BpfInterpreter::with_memory_config(self.memory_config())?;
+        interpreter.set_compute_units_limit(self.compute_unit_limit);
+        Ok(interpreter)
+    }
+}
+
+impl Default for ComputeBudget {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: DEFAULT_COMPUTE_UNITS,
+            compute_unit_price: 0,
+            heap_size: DEFAULT_HEAP_SIZE,
+        }
+    }
+}
+
+fn invalid_budget(message: String) -> TranspilerError {
+    TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidComputeBudget { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget_ix(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut data = vec![tag];
+        data.extend_from_slice(value);
+        data
+    }
+
+    #[test]
+    fn test_defaults_without_budget_instructions() {
+        let other_program = [9u8; 32];
+        let budget = ComputeBudget::from_instructions([(&other_program, &[2u8, 0, 0, 0, 0][..])]).unwrap();
+        assert_eq!(budget, ComputeBudget::default());
+        assert_eq!(budget.compute_unit_limit, 200_000);
+    }
+
+    #[test]
+    fn test_each_instruction_adjusts_limits() {
+        let heap = budget_ix(1, &(64 * 1024u32).to_le_bytes());
+        let limit = budget_ix(2, &50_000u32.to_le_bytes());
+        let price = budget_ix(3, &7u64.to_le_bytes());
+
+        let budget = ComputeBudget::from_instructions([(&COMPUTE_BUDGET_PROGRAM_ID, heap.as_slice())]).unwrap();
+        assert_eq!(budget.heap_size, 64 * 1024);
+
+        let budget = ComputeBudget::from_instructions([(&COMPUTE_BUDGET_PROGRAM_ID, limit.as_slice())]).unwrap();
+        assert_eq!(budget.compute_unit_limit, 50_000);
+
+        let budget = ComputeBudget::from_instructions([(&COMPUTE_BUDGET_PROGRAM_ID, price.as_slice())]).unwrap();
+        assert_eq!(budget.compute_unit_price, 7);
+
+        let interpreter = ComputeBudget::from_instructions([
+            (&COMPUTE_BUDGET_PROGRAM_ID, heap.as_slice()),
+            (&COMPUTE_BUDGET_PROGRAM_ID, limit.as_slice()),
+        ])
+        .unwrap()
+        .build_interpreter()
+        .unwrap();
+        assert_eq!(interpreter.compute_units_remaining(), 50_000);
+        assert_eq!(interpreter.memory().region_slice(crate::MemoryRegionType::Heap).unwrap().len(), 64 * 1024);
+    }
+
+    #[test]
+    fn test_invalid_budget_instructions() {
+        let limit = budget_ix(2, &u32::MAX.to_le_bytes());
+        let budget = ComputeBudget::from_instructions([(&COMPUTE_BUDGET_PROGRAM_ID, limit.as_slice())]).unwrap();
+        assert_eq!(budget.compute_unit_limit, MAX_COMPUTE_UNIT_LIMIT as u64);
+
+        let duplicate = [(&COMPUTE_BUDGET_PROGRAM_ID, limit.as_slice()); 2];
+        assert!(ComputeBudget::from_instructions(duplicate).is_err());
+
+        let odd_heap = budget_ix(1, &(40_000u32).to_le_bytes());
+        assert!(ComputeBudget::from_instructions([(&COMPUTE_BUDGET_PROGRAM_ID, odd_heap.as_slice())]).is_err());
+        assert!(ComputeBudgetInstruction::parse(&[3, 1, 2]).is_err());
+        assert!(ComputeBudgetInstruction::parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+//! Opcode, instruction and ALU definitions shared with `no_std` guests.
+//!
+//! This module depends only on `core`, so a guest crate can include it
+//! with `#[path = ".../core_bpf.rs"] mod core_bpf;`.
+
+/// eBPF uses only the low 6 bits of a 64-bit shift amount
+pub const SHIFT_MASK_64: u64 = 63;
+/// and the low 5 bits of a 32-bit one
+pub const SHIFT_MASK_32: u32 = 31;
+
+/// BPF instruction structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BpfInstruction {
+    pub opcode: BpfOpcode,
+    pub dst_reg: u8,
+    pub src_reg: u8,
+    pub immediate: i64,
+    pub offset: i16,
+}
+
+impl BpfInstruction {
+    /// 8-byte little-endian encoding; only the low 32 bits of the immediate are kept
+    pub fn encode(&self) -> [u8; 8] {
+        let mut slot = [0u8; 8];
+        slot[0] = self.opcode.code();
+        slot[1] = (self.src_reg << 4) | (self.dst_reg & 0x0f);
+        slot[2..4].copy_from_slice(&self.offset.to_le_bytes());
+        slot[4..8].copy_from_slice(&(self.immediate as u32).to_le_bytes());
+        slot
+    }
+
+    /// Two-slot encoding used by `LD_IMM64`, with `high` as the upper 32 bits
+    pub fn encode_wide(&self, high: u32) -> [u8; 16] {
+        let mut slots = [0u8; 16];
+        slots[..8].copy_from_slice(&self.encode());
+        slots[12..16].copy_from_slice(&high.to_le_bytes());
+        slots
+    }
+}
+
+/// Defines [`BpfOpcode`] and both directions of its byte encoding from one table
+macro_rules! bpf_opcodes {
+    ($($name:ident = $code:literal,)*) => {
+        /// BPF opcodes supported by our transpiler
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum BpfOpcode {
+            $($name,)*
+            /// Opcode byte the parser does not recognize (only produced on request)
+            Unknown(u8),
+        }
+
+        impl BpfOpcode {
+            /// Opcode for an encoded byte, or `None` if it is not recognized
+            pub fn from_code(code: u8) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Encoded opcode byte
+            pub fn code(self) -> u8 {
+                match self {
+                    $(Self::$name => $code,)*
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+    };
+}
+
+bpf_opcodes! {
+    // 32-bit ALU operations; results are zero-extended to 64 bits
+    Add32Imm = 0x04,      // ADD32_IMM
+    Add32Reg = 0x0c,      // ADD32_REG
+    Sub32Imm = 0x14,      // SUB32_IMM
+    Sub32Reg = 0x1c,      // SUB32_REG
+    Mul32Imm = 0x24,      // MUL32_IMM
+    Mul32Reg = 0x2c,      // MUL32_REG
+    Div32Imm = 0x34,      // DIV32_IMM
+    Div32Reg = 0x3c,      // DIV32_REG
+    Or32Imm = 0x44,       // OR32_IMM
+    Or32Reg = 0x4c,       // OR32_REG
+    And32Imm = 0x54,      // AND32_IMM
+    And32Reg = 0x5c,      // AND32_REG
+    Lsh32Imm = 0x64,      // LSH32_IMM
+    Lsh32Reg = 0x6c,      // LSH32_REG
+    Rsh32Imm = 0x74,      // RSH32_IMM
+    Rsh32Reg = 0x7c,      // RSH32_REG
+    Neg32 = 0x84,         // NEG32
+    Mod32Imm = 0x94,      // MOD32_IMM
+    Mod32Reg = 0x9c,      // MOD32_REG
+    Xor32Imm = 0xa4,      // XOR32_IMM
+    Xor32Reg = 0xac,      // XOR32_REG
+    Mov32Imm = 0xb4,      // MOV32_IMM
+    Mov32Reg = 0xbc,      // MOV32_REG
+    Arsh32Imm = 0xc4,     // ARSH32_IMM
+    Arsh32Reg = 0xcc,     // ARSH32_REG
+
+    // ALU operations
+    Add64Imm = 0x07,      // ADD64_IMM
+    Add64Reg = 0x0f,      // ADD64_REG
+    Sub64Imm = 0x17,      // SUB64_IMM
+    Sub64Reg = 0x1f,      // SUB64_REG
+    Mul64Imm = 0x27,      // MUL64_IMM
+    Mul64Reg = 0x2f,      // MUL64_REG
+    Div64Imm = 0x37,      // DIV64_IMM
+    Div64Reg = 0x3f,      // DIV64_REG
+    Or64Imm = 0x47,       // OR64_IMM
+    Or64Reg = 0x4f,       // OR64_REG
+    And64Imm = 0x57,      // AND64_IMM
+    And64Reg = 0x5f,      // AND64_REG
+    Lsh64Imm = 0x67,      // LSH64_IMM
+    Lsh64Reg = 0x6f,      // LSH64_REG
+    Rsh64Imm = 0x77,      // RSH64_IMM
+    Rsh64Reg = 0x7f,      // RSH64_REG
+    Neg64 = 0x87,         // NEG64
+    Mod64Imm = 0x97,      // MOD64_IMM
+    Mod64Reg = 0x9f,      // MOD64_REG
+    Xor64Imm = 0xa7,      // XOR64_IMM
+    Xor64Reg = 0xaf,      // XOR64_REG
+    Mov64Imm = 0xb7,      // MOV64_IMM
+    Mov64Reg = 0xbf,      // MOV64_REG
+    Arsh64Imm = 0xc7,     // ARSH64_IMM
+    Arsh64Reg = 0xcf,     // ARSH64_REG
+
+    // Memory operations
+    LdImm64 = 0x18,       // LD_IMM64
+    LdAbs8 = 0x30,        // LD_ABS8
+    LdAbs16 = 0x28,       // LD_ABS16
+    LdAbs32 = 0x20,       // LD_ABS32
+    LdAbs64 = 0x19,       // LD_ABS64 (different from LD_IMM64)
+    LdInd8 = 0x38,        // LD_IND8
+    LdInd16 = 0x31,       // LD_IND16 (different from LD_ABS8)
+    LdInd32 = 0x29,       // LD_IND32 (different from LD_ABS16)
+    LdInd64 = 0x21,       // LD_IND64 (different from LD_ABS32)
+    Ldx8 = 0x71,          // LDX8
+    Ldx16 = 0x69,         // LDX16
+    Ldx32 = 0x61,         // LDX32
+    Ldx64 = 0x79,         // LDX64
+    St8 = 0x72,           // ST8
+    St16 = 0x6a,          // ST16
+    St32 = 0x62,          // ST32
+    St64 = 0x7a,          // ST64
+    Stx8 = 0x73,          // STX8
+    Stx16 = 0x6b,         // STX16
+    Stx32 = 0x63,         // STX32
+    Stx64 = 0x7b,         // STX64
+
+    // Branch operations
+    Ja = 0x05,            // JA
+    JeqImm = 0x15,        // JEQ_IMM
+    JeqReg = 0x1d,        // JEQ_REG
+    JgtImm = 0x25,        // JGT_IMM
+    JgtReg = 0x2d,        // JGT_REG
+    JgeImm = 0x35,        // JGE_IMM
+    JgeReg = 0x3d,        // JGE_REG
+    JltImm = 0xa5,        // JLT_IMM
+    JltReg = 0xad,        // JLT_REG
+    JleImm = 0xb5,        // JLE_IMM
+    JleReg = 0xbd,        // JLE_REG
+    JsetImm = 0x45,       // JSET_IMM
+    JsetReg = 0x4d,       // JSET_REG
+    JneImm = 0x55,        // JNE_IMM
+    JneReg = 0x5d,        // JNE_REG
+    JsgtImm = 0x65,       // JSGT_IMM
+    JsgtReg = 0x6d,       // JSGT_REG
+    JsgeImm = 0x75,       // JSGE_IMM
+    JsgeReg = 0x7d,       // JSGE_REG
+    JsltImm = 0xc5,       // JSLT_IMM
+    JsltReg = 0xcd,       // JSLT_REG
+    JsleImm = 0xd5,       // JSLE_IMM
+    JsleReg = 0xdd,       // JSLE_REG
+    Call = 0x85,          // CALL
+    Exit = 0x95,          // EXIT
+}
+
+/// Instruction class, the low 3 bits of the opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BpfClass {
+    Ld,
+    Ldx,
+    St,
+    Stx,
+    Alu,
+    Jmp,
+    Jmp32,
+    Alu64,
+}
+
+impl BpfOpcode {
+    /// Instruction class encoded in the opcode byte
+    pub fn class(self) -> BpfClass {
+        match self.code() & 0x07 {
+            0x00 => BpfClass::Ld,
+            0x01 => BpfClass::Ldx,
+            0x02 => BpfClass::St,
+            0x03 => BpfClass::Stx,
+            0x04 => BpfClass::Alu,
+            0x05 => BpfClass::Jmp,
+            0x06 => BpfClass::Jmp32,
+            _ => BpfClass::Alu64,
+        }
+    }
+
+    /// Whether this is a 64-bit ALU instruction (class `BPF_ALU64`)
+    pub fn is_alu64(self) -> bool {
+        !matches!(self, Self::Unknown(_)) && self.class() == BpfClass::Alu64
+    }
+
+    /// Whether this is a 32-bit ALU instruction (class `BPF_ALU`)
+    pub fn is_alu32(self) -> bool {
+        !matches!(self, Self::Unknown(_)) && self.class() == BpfClass::Alu
+    }
+
+    /// Whether the second operand is `src_reg` rather than the immediate (`BPF_X`)
+    pub fn uses_src_register(self) -> bool {
+        self.code() & 0x08 != 0
+    }
+
+    /// Whether the second operand is the immediate (`BPF_K`)
+    pub fn is_immediate_source(self) -> bool {
+        !self.uses_src_register()
+    }
+
+    /// Whether this is a known conditional or unconditional branch (not `CALL`/`EXIT`)
+    pub fn is_jump(self) -> bool {
+        matches!(self.class(), BpfClass::Jmp | BpfClass::Jmp32) 
+            && !matches!(self, Self::Call | Self::Exit | Self::Unknown(_))
+    }
+
+    /// Whether this writes memory (`ST`/`STX`)
+    pub fn is_store(self) -> bool {
+        matches!(self.class(), BpfClass::St | BpfClass::Stx)
+    }
+
+    /// Whether this loads into a register (`LD`/`LDX`, including `LD_IMM64`)
+    pub fn is_load(self) -> bool {
+        matches!(self.class(), BpfClass::Ld | BpfClass::Ldx)
+    }
+}
+
+/// Decode one 8-byte instruction slot
+///
+/// Unrecognized opcodes decode as `BpfOpcode::Unknown`. `LD_IMM64` yields
+/// only its low word; the high word lives in the following slot.
+pub fn decode_slot(slot: &[u8; 8]) -> BpfInstruction {
+    BpfInstruction {
+        opcode: BpfOpcode::from_code(slot[0]).unwrap_or(BpfOpcode::Unknown(slot[0])),
+        dst_reg: slot[1] & 0x0f,
+        src_reg: slot[1] >> 4,
+        offset: i16::from_le_bytes([slot[2], slot[3]]),
+        // eBPF immediates are signed 32-bit values sign-extended to 64 bits
+        immediate: i32::from_le_bytes([slot[4], slot[5], slot[6], slot[7]]) as i64,
+    }
+}
+
+/// Fault raised by ALU semantics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluFault {
+    DivisionByZero,
+}
+
+/// Result of a 64-bit ALU `opcode` on `dst` and `operand`
+///
+/// `operand` is the source register or sign-extended immediate, as the
+/// opcode selects. Returns `None` for opcodes outside the 64-bit ALU class.
+pub fn alu64(opcode: BpfOpcode, dst: u64, operand: u64) -> Option<Result<u64, AluFault>> {
+    use BpfOpcode::*;
+
+    let shift = operand & SHIFT_MASK_64;
+    let result = match opcode {
+        Add64Imm | Add64Reg => dst.wrapping_add(operand),
+        Sub64Imm | Sub64Reg => dst.wrapping_sub(operand),
+        Mul64Imm | Mul64Reg => dst.wrapping_mul(operand),
+        Div64Imm | Div64Reg | Mod64Imm | Mod64Reg if operand == 0 => {
+            return Some(Err(AluFault::DivisionByZero));
+        }
+        Div64Imm | Div64Reg => dst / operand,
+        Mod64Imm | Mod64Reg => dst % operand,
+        Or64Imm | Or64Reg => dst | operand,
+        And64Imm | And64Reg => dst & operand,
+        Xor64Imm | Xor64Reg => dst ^ operand,
+        Lsh64Imm | Lsh64Reg => dst << shift,
+        Rsh64Imm | Rsh64Reg => dst >> shift,
+        Arsh64Imm | Arsh64Reg => ((dst as i64) >> shift) as u64,
+        Neg64 => dst.wrapping_neg(),
+        Mov64Imm | Mov64Reg => operand,
+        _ => return None,
+    };
+    Some(Ok(result))
+}
+
+/// Unsigned wraparound in an arithmetic instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverflowKind {
+    Add,
+    Sub,
+    Mul,
+}
+
+/// Whether an ALU `opcode` on `dst` and `operand` wraps, treating both as unsigned
+///
+/// 32-bit opcodes are checked on the low halves. Only add, sub and mul are
+/// considered; other opcodes return `None`.
+pub fn alu_overflow(opcode: BpfOpcode, dst: u64, operand: u64) -> Option<OverflowKind> {
+    use BpfOpcode::*;
+
+    let (kind, wrapped) = match opcode {
+        Add64Imm | Add64Reg => (OverflowKind::Add, dst.overflowing_add(operand).1),
+        Sub64Imm | Sub64Reg => (OverflowKind::Sub, dst.overflowing_sub(operand).1),
+        Mul64Imm | Mul64Reg => (OverflowKind::Mul, dst.overflowing_mul(operand).1),
+        Add32Imm | Add32Reg => (OverflowKind::Add, (dst as u32).overflowing_add(operand as u32).1),
+        Sub32Imm | Sub32Reg => (OverflowKind::Sub, (dst as u32).overflowing_sub(operand as u32).1),
+        Mul32Imm | Mul32Reg => (OverflowKind::Mul, (dst as u32).overflowing_mul(operand as u32).1),
+        _ => return None,
+    };
+    wrapped.then_some(kind)
+}
+
+/// Result of a 32-bit ALU `opcode` on the low halves of `dst` and `operand`
+///
+/// The 32-bit result is zero-extended, so the upper half of `dst` is always
+/// cleared. Returns `None` for opcodes outside the 32-bit ALU class.
+pub fn alu32(opcode: BpfOpcode, dst: u64, operand: u64) -> Option<Result<u64, AluFault>> {
+    use BpfOpcode::*;
+
+    let (dst, operand) = (dst as u32, operand as u32);
+    let shift = operand & SHIFT_MASK_32;
+    let result = match opcode {
+        Add32Imm | Add32Reg => dst.wrapping_add(operand),
+        Sub32Imm | Sub32Reg => dst.wrapping_sub(operand),
+        Mul32Imm | Mul32Reg => dst.wrapping_mul(operand),
+        Div32Imm | Div32Reg | Mod32Imm | Mod32Reg if operand == 0 => {
+            return Some(Err(AluFault::DivisionByZero));
+        }
+        Div32Imm | Div32Reg => dst / operand,
+        Mod32Imm | Mod32Reg => dst % operand,
+        Or32Imm | Or32Reg => dst | operand,
+        And32Imm | And32Reg => dst & operand,
+        Xor32Imm | Xor32Reg => dst ^ operand,
+        Lsh32Imm | Lsh32Reg => dst << shift,
+        Rsh32Imm | Rsh32Reg => dst >> shift,
+        Arsh32Imm | Arsh32Reg => ((dst as i32) >> shift) as u32,
+        Neg32 => dst.wrapping_neg(),
+        Mov32Imm | Mov32Reg => operand,
+        _ => return None,
+    };
+    Some(Ok(result as u64))
+}
+
+/// Whether a conditional jump `opcode` on `dst` and `operand` is taken
+///
+/// `Jsgt`/`Jsge`/`Jslt`/`Jsle` compare as signed 64-bit values, the rest as
+/// unsigned. Returns `None` for `JA` and non-jump opcodes.
+pub fn jump_taken(opcode: BpfOpcode, dst: u64, operand: u64) -> Option<bool> {
+    use BpfOpcode::*;
+
+    let (signed_dst, signed_operand) = (dst as i64, operand as i64);
+    let taken = match opcode {
+        JeqImm | JeqReg => dst == operand,
+        JneImm | JneReg => dst != operand,
+        JgtImm | JgtReg => dst > operand,
+        JgeImm | JgeReg => dst >= operand,
+        JltImm | JltReg => dst < operand,
+        JleImm | JleReg => dst <= operand,
+        JsetImm | JsetReg => dst & operand != 0,
+        JsgtImm | JsgtReg => signed_dst > signed_operand,
+        JsgeImm | JsgeReg => signed_dst >= signed_operand,
+        JsltImm | JsltReg => signed_dst < signed_operand,
+        JsleImm | JsleReg => signed_dst <= signed_operand,
+        _ => return None,
+    };
+    Some(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code_round_trips() {
+        for code in 0..=u8::MAX {
+            if let Some(opcode) = BpfOpcode::from_code(code) {
+                assert_eq!(opcode.code(), code);
+            }
+        }
+        assert_eq!(BpfOpcode::from_code(0xff), None);
+    }
+
+    #[test]
+    fn test_decode_slot_fixtures() {
+        let insn = |opcode, dst_reg, src_reg, offset, immediate| BpfInstruction {
+            opcode,
+            dst_reg,
+            src_reg,
+            immediate,
+            offset,
+        };
+        let fixtures = [
+            ([0xb7, 0x00, 0x00, 0x00, 0x2a, 0x00, 0x00, 0x00], insn(BpfOpcode::Mov64Imm, 0, 0, 0, 42)),
+            ([0x0f, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], insn(BpfOpcode::Add64Reg, 1, 2, 0, 0)),
+            ([0x07, 0x01, 0x00, 0x00, 0xf8, 0xff, 0xff, 0xff], insn(BpfOpcode::Add64Imm, 1, 0, 0, -8)),
+            ([0x7a, 0x0a, 0xf8, 0xff, 0x2a, 0x00, 0x00, 0x00], insn(BpfOpcode::St64, 10, 0, -8, 42)),
+            ([0x15, 0x03, 0xfe, 0xff, 0x01, 0x00, 0x00, 0x00], insn(BpfOpcode::JeqImm, 3, 0, -2, 1)),
+            ([0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], insn(BpfOpcode::Exit, 0, 0, 0, 0)),
+            ([0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], insn(BpfOpcode::Unknown(0xff), 0, 0, 0, 0)),
+        ];
+
+        for (slot, expected) in fixtures {
+            assert_eq!(decode_slot(&slot), expected);
+            assert_eq!(expected.encode(), slot);
+        }
+    }
+
+    #[test]
+    fn test_opcode_class_and_source() {
+        assert_eq!(BpfOpcode::Add64Reg.class(), BpfClass::Alu64);
+        assert!(!BpfOpcode::Add64Reg.is_immediate_source());
+        assert!(BpfOpcode::Add64Imm.is_immediate_source());
+
+        assert_eq!(BpfOpcode::LdImm64.class(), BpfClass::Ld);
+        assert_eq!(BpfOpcode::Ldx32.class(), BpfClass::Ldx);
+        assert_eq!(BpfOpcode::St16.class(), BpfClass::St);
+        assert_eq!(BpfOpcode::Stx64.class(), BpfClass::Stx);
+        assert_eq!(BpfOpcode::Exit.class(), BpfClass::Jmp);
+
+        assert!(BpfOpcode::Ja.is_jump() && BpfOpcode::JsetReg.is_jump());
+        assert!(!BpfOpcode::Call.is_jump() && !BpfOpcode::Exit.is_jump());
+        assert!(BpfOpcode::St8.is_store() && BpfOpcode::Stx8.is_store() && !BpfOpcode::Ldx8.is_store());
+        assert!(BpfOpcode::LdImm64.is_load() && BpfOpcode::Ldx8.is_load() && !BpfOpcode::Mov64Imm.is_load());
+    }
+
+    #[test]
+    fn test_alu32_clears_upper_bits() {
+        let dst = 0xdead_beef_0000_0001;
+        assert_eq!(alu32(BpfOpcode::Add32Imm, dst, 1), Some(Ok(2)));
+        assert_eq!(alu32(BpfOpcode::Add32Imm, 0xffff_ffff, 1), Some(Ok(0)));
+        assert_eq!(alu32(BpfOpcode::Mul32Reg, dst | 0x1_0000, 0x1_0000), Some(Ok(0x1_0000)));
+        assert_eq!(alu32(BpfOpcode::Mov32Imm, dst, -1i64 as u64), Some(Ok(0xffff_ffff)));
+        assert_eq!(alu32(BpfOpcode::Arsh32Imm, 0x8000_0000, 4), Some(Ok(0xf800_0000)));
+        assert_eq!(alu32(BpfOpcode::Lsh32Reg, 1, 33), Some(Ok(2)));
+        assert_eq!(alu32(BpfOpcode::Div32Reg, 7, 1 << 32), Some(Err(AluFault::DivisionByZero)));
+        assert_eq!(alu32(BpfOpcode::Add64Imm, 1, 1), None);
+        assert!(BpfOpcode::Neg32.is_alu32() && !BpfOpcode::Neg32.is_alu64());
+    }
+
+    #[test]
+    fn test_alu_overflow() {
+        assert_eq!(alu_overflow(BpfOpcode::Add64Reg, u64::MAX, 1), Some(OverflowKind::Add));
+        assert_eq!(alu_overflow(BpfOpcode::Sub32Imm, 1 << 32, 1), Some(OverflowKind::Sub));
+        assert_eq!(alu_overflow(BpfOpcode::Mul64Imm, 1 << 32, 1 << 31), None);
+        assert_eq!(alu_overflow(BpfOpcode::Lsh64Imm, u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_alu64_semantics() {
+        assert_eq!(alu64(BpfOpcode::Sub64Imm, 1, 2), Some(Ok(u64::MAX)));
+        assert_eq!(alu64(BpfOpcode::Div64Reg, 7, 0), Some(Err(AluFault::DivisionByZero)));
+        assert_eq!(alu64(BpfOpcode::Lsh64Imm, 1, 65), Some(Ok(2)));
+        assert_eq!(alu64(BpfOpcode::Arsh64Reg, (-8i64) as u64, 1), Some(Ok((-4i64) as u64)));
+        assert_eq!(alu64(BpfOpcode::Exit, 0, 0), None);
+    }
+
+    #[test]
+    fn test_jump_taken_signedness() {
+        let minus_one = -1i64 as u64;
+        assert_eq!(jump_taken(BpfOpcode::JltImm, minus_one, 1), Some(false));
+        assert_eq!(jump_taken(BpfOpcode::JsltImm, minus_one, 1), Some(true));
+        assert_eq!(jump_taken(BpfOpcode::JleReg, 5, 5), Some(true));
+        assert_eq!(jump_taken(BpfOpcode::JsleReg, 0, minus_one), Some(false));
+        assert_eq!(jump_taken(BpfOpcode::JgeImm, minus_one, 0), Some(true));
+        assert_eq!(jump_taken(BpfOpcode::JsgeImm, minus_one, 0), Some(false));
+        assert_eq!(jump_taken(BpfOpcode::Ja, 0, 0), None);
+        assert_eq!(jump_taken(BpfOpcode::Add64Imm, 0, 0), None);
+    }
+}
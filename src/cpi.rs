@@ -0,0 +1,326 @@
+use crate::bpf_interpreter::BpfInterpreter;
+use crate::error::{InterpreterError, MemoryError, MemoryErrorKind, TranspilerError};
+use crate::program_registry::ProgramRegistry;
+use crate::syscalls::{LogLevel, SyscallContext, SyscallHandler, SOL_INVOKE_SIGNED_C};
+use crate::wire::read_u64_le;
+use std::rc::Rc;
+
+/// Maximum nesting of cross-program invocations below the top-level program
+pub const MAX_CPI_DEPTH: usize = 4;
+
+/// Size of a C-ABI `SolInstruction { program_id, accounts, account_len, data, data_len }`
+const SOL_INSTRUCTION_SIZE: u64 = 40;
+/// Size of a C-ABI `SolAccountMeta { pubkey, is_writable, is_signer }`, including padding
+const SOL_ACCOUNT_META_SIZE: u64 = 16;
+
+/// An account available to a program, with the privileges it was granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: [u8; 32],
+    /// Virtual address of the account's mapped data
+    pub address: u64,
+    pub is_writable: bool,
+    pub is_signer: bool,
+}
+
+/// Syscall handler running `sol_invoke_signed_c` targets from a [`ProgramRegistry`]
+///
+/// The callee runs in a fresh interpreter holding copies of the accounts
+/// named in the instruction's metas, with the instruction data mapped at
+/// `MM_INPUT_START`. A meta may only ask for writable or signer privileges
+/// the caller holds; the callee's copy of a read-only account is mapped
+/// read-only. On success, writable accounts are copied back and the
+/// callee's logs and return data are passed to the caller. Signer seeds
+/// are not checked, so a program cannot sign for a PDA, and the callee
+/// gets its own compute budget.
+pub struct CpiSyscalls {
+    registry: Rc<ProgramRegistry>,
+    depth: usize,
+    accounts: Vec<AccountMeta>,
+}
+
+impl CpiSyscalls {
+    /// Create a handler for a top-level program without accounts
+    pub fn new(registry: Rc<ProgramRegistry>) -> Self {
+        Self::with_accounts(registry, Vec::new())
+    }
+
+    /// Create a handler for a top-level program holding `accounts`
+    pub fn with_accounts(registry: Rc<ProgramRegistry>, accounts: Vec<AccountMeta>) -> Self {
+        Self { registry, depth: 0, accounts }
+    }
+
+    /// Check a `SolAccountMeta` against the caller's privileges
+    fn grant(&self, ctx: &SyscallContext, meta: &[u8]) -> Result<AccountMeta, TranspilerError> {
+        let pubkey: [u8; 32] = ctx.read_memory(read_u64_le(meta, 0).unwrap(), 32)?.try_into().unwrap();
+        let (is_writable, is_signer) = (meta[8] != 0, meta[9] != 0);
+        let account: String = pubkey.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let caller = self
+            .accounts
+            .iter()
+            .find(|granted| granted.pubkey == pubkey)
+            .ok_or_else(|| {
+                TranspilerError::InterpreterError(InterpreterError::CpiUnknownAccount { account: account.clone() })
+            })?;
+
+        for (requested, held, privilege) in
+            [(is_writable, caller.is_writable, "writable"), (is_signer, caller.is_signer, "signer")]
+        {
+            if requested && !held {
+                return Err(TranspilerError::InterpreterError(InterpreterError::CpiPrivilegeEscalation {
+                    account,
+                    privilege,
+                }));
+            }
+        }
+        Ok(AccountMeta { is_writable, is_signer, ..*caller })
+    }
+
+    fn invoke(&self, ctx: &mut SyscallContext) -> Result<(), TranspilerError> {
+        if self.depth >= MAX_CPI_DEPTH {
+            return Err(TranspilerError::InterpreterError(InterpreterError::CpiDepthExceeded {
+                max_depth: MAX_CPI_DEPTH,
+            }));
+        }
+
+        let descriptor = ctx.read_memory(ctx.registers[1], SOL_INSTRUCTION_SIZE)?;
+        let field = |index: usize| read_u64_le(descriptor, index * 8).unwrap();
+        let (program_id_addr, accounts_addr, account_len, data_addr, data_len) =
+            (field(0), field(1), field(2), field(3), field(4));
+
+        let program_id: [u8; 32] = ctx.read_memory(program_id_addr, 32)?.try_into().unwrap();
+        let metas_len = account_len.checked_mul(SOL_ACCOUNT_META_SIZE).ok_or(MemoryError {
+            addr: accounts_addr,
+            size: usize::MAX,
+            kind: MemoryErrorKind::OutOfBounds,
+        })?;
+        let metas = ctx.read_memory(accounts_addr, metas_len)?.to_vec();
+        let granted = metas
+            .chunks_exact(SOL_ACCOUNT_META_SIZE as usize)
+            .map(|meta| self.grant(ctx, meta))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = ctx.read_memory(data_addr, data_len)?.to_vec();
+
+        let mut callee = BpfInterpreter::with_syscalls(Box::new(Self {
+            registry: Rc::clone(&self.registry),
+            depth: self.depth + 1,
+            accounts: granted.clone(),
+        }));
+        callee.set_sysvars(*ctx.sysvars);
+        callee.set_max_return_data(ctx.max_return_data);
+        for account in &granted {
+            let contents = ctx.memory.account_snapshot(account.address).unwrap_or_default();
+            let owner = ctx.memory.account_owner(account.address);
+            callee.memory_mut().map_owned_account(account.address, contents, account.is_writable, owner);
+        }
+        callee.memory_mut().map_input(data);
+
+        let callee_name: String = program_id.iter().map(|byte| format!("{:02x}", byte)).collect();
+        ctx.log(LogLevel::Debug, format!("Program {} invoke [{}]", callee_name, self.depth + 2));
+        let result = self.registry.execute_instruction(&program_id, &mut callee)?;
+        if result.exit_code != 0 {
+            return Err(TranspilerError::InterpreterError(InterpreterError::CpiFailed {
+                exit_code: result.exit_code,
+            }));
+        }
+
+        for account in granted.iter().filter(|account| account.is_writable) {
+            if let Some(contents) = callee.memory().account_snapshot(account.address) {
+                ctx.memory.write_account_data(account.address, &contents)?;
+            }
+        }
+        ctx.logs.extend_from_slice(callee.logs());
+        ctx.log(LogLevel::Debug, format!("Program {} success", callee_name));
+        *ctx.return_data = callee.return_data().clone();
+        Ok(())
+    }
+}
+
+impl SyscallHandler for CpiSyscalls {
+    fn dispatch(&mut self, ctx: &mut SyscallContext, id: u64) -> Result<bool, TranspilerError> {
+        if id != SOL_INVOKE_SIGNED_C {
+            return Ok(false);
+        }
+
+        self.invoke(ctx)?;
+        ctx.registers[0] = 0;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syscalls::{SOL_GET_RETURN_DATA, SOL_SET_RETURN_DATA};
+    use crate::ExecutionResult;
+
+    const CALLER_ID: [u8; 32] = [1; 32];
+    const CALLEE_ID: [u8; 32] = [2; 32];
+    /// Caller-owned account holding the instruction descriptor and output buffers
+    const SCRATCH: u64 = 0x5_0000_0000;
+
+    fn lddw(dst: u8, value: u64) -> Vec<u8> {
+        let mut bytes = vec![0x18, dst, 0, 0];
+        bytes.extend_from_slice(&(value as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&((value >> 32) as u32).to_le_bytes());
+        bytes
+    }
+
+    fn call(id: u64) -> Vec<u8> {
+        let mut bytes = vec![0x85, 0, 0, 0];
+        bytes.extend_from_slice(&(id as u32).to_le_bytes());
+        bytes
+    }
+
+    const EXIT: [u8; 8] = [0x95, 0, 0, 0, 0, 0, 0, 0];
+
+    /// `SolInstruction` targeting `program_id` with no accounts or data, plus
+    /// room for 8 bytes of return data and the returning program id
+    fn scratch_account(program_id: [u8; 32]) -> Vec<u8> {
+        let mut account = Vec::new();
+        for field in [SCRATCH + 40, SCRATCH, 0, SCRATCH, 0] {
+            account.extend_from_slice(&field.to_le_bytes());
+        }
+        account.extend_from_slice(&program_id);
+        account.resize(112, 0);
+        account
+    }
+
+    /// Account passed through the CPI in the privilege tests
+    const TARGET: u64 = 0x6_0000_0000;
+    const TARGET_KEY: [u8; 32] = [7; 32];
+
+    /// Like [`scratch_account`], with one `SolAccountMeta` naming `pubkey`
+    fn scratch_with_meta(program_id: [u8; 32], pubkey: [u8; 32], is_writable: bool, is_signer: bool) -> Vec<u8> {
+        let mut account = scratch_account(program_id);
+        account[8..24].copy_from_slice(&[(SCRATCH + 112).to_le_bytes(), 1u64.to_le_bytes()].concat());
+        account.extend_from_slice(&(SCRATCH + 128).to_le_bytes());
+        account.extend_from_slice(&[is_writable as u8, is_signer as u8, 0, 0, 0, 0, 0, 0]);
+        account.extend_from_slice(&pubkey);
+        account
+    }
+
+    /// Caller holding [`TARGET`] with the given privileges, invoking a callee that writes it
+    fn privilege_test(held: (bool, bool), requested: (bool, bool)) -> (BpfInterpreter, Result<ExecutionResult, TranspilerError>) {
+        let callee = [
+            lddw(1, TARGET),
+            vec![0xb7, 0x02, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00], // MOV64_IMM R2, 1
+            vec![0x7b, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // STX64 [R1], R2
+            vec![0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // MOV64_IMM R0, 0
+            EXIT.to_vec(),
+        ]
+        .concat();
+        let caller = [lddw(1, SCRATCH), call(SOL_INVOKE_SIGNED_C), EXIT.to_vec()].concat();
+        let mut registry = ProgramRegistry::new();
+        registry.load(CALLER_ID, caller).unwrap();
+        registry.load(CALLEE_ID, callee).unwrap();
+
+        let registry = Rc::new(registry);
+        let target = AccountMeta { pubkey: TARGET_KEY, address: TARGET, is_writable: held.0, is_signer: held.1 };
+        let mut interpreter =
+            BpfInterpreter::with_syscalls(Box::new(CpiSyscalls::with_accounts(Rc::clone(&registry), vec![target])));
+        let scratch = scratch_with_meta(CALLEE_ID, TARGET_KEY, requested.0, requested.1);
+        interpreter.memory_mut().map_account(SCRATCH, scratch, true);
+        interpreter.memory_mut().map_account(TARGET, vec![0; 8], held.0);
+        let result = registry.execute_instruction(&CALLER_ID, &mut interpreter);
+        (interpreter, result)
+    }
+
+    fn caller_interpreter(registry: ProgramRegistry, target: [u8; 32]) -> (Rc<ProgramRegistry>, BpfInterpreter) {
+        let registry = Rc::new(registry);
+        let mut interpreter = BpfInterpreter::with_syscalls(Box::new(CpiSyscalls::new(Rc::clone(&registry))));
+        interpreter.memory_mut().map_account(SCRATCH, scratch_account(target), true);
+        (registry, interpreter)
+    }
+
+    #[test]
+    fn test_cpi_return_data_reaches_caller() {
+        let callee = [
+            vec![0x7a, 0x0a, 0xf8, 0xff, 0x2a, 0x00, 0x00, 0x00], // ST64 [R10-8], 42
+            vec![0xbf, 0xa1, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // MOV64_REG R1, R10
+            vec![0x07, 0x01, 0x00, 0x00, 0xf8, 0xff, 0xff, 0xff], // ADD64_IMM R1, -8
+            vec![0xb7, 0x02, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00], // MOV64_IMM R2, 8
+            call(SOL_SET_RETURN_DATA),
+            vec![0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // MOV64_IMM R0, 0
+            EXIT.to_vec(),
+        ]
+        .concat();
+        let caller = [
+            lddw(1, SCRATCH),
+            call(SOL_INVOKE_SIGNED_C),
+            lddw(1, SCRATCH + 72),
+            vec![0xb7, 0x02, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00], // MOV64_IMM R2, 8
+            lddw(3, SCRATCH + 80),
+            call(SOL_GET_RETURN_DATA),
+            EXIT.to_vec(),
+        ]
+        .concat();
+
+        let mut registry = ProgramRegistry::new();
+        registry.load(CALLER_ID, caller).unwrap();
+        registry.load(CALLEE_ID, callee).unwrap();
+        let (registry, mut interpreter) = caller_interpreter(registry, CALLEE_ID);
+
+        let result = registry.execute_instruction(&CALLER_ID, &mut interpreter).unwrap();
+        assert_eq!(result.exit_code, 8);
+        assert_eq!(interpreter.return_data().program_id, CALLEE_ID);
+        let levels: Vec<LogLevel> = interpreter.logs().iter().map(|entry| entry.level).collect();
+        assert_eq!(levels, [LogLevel::Debug, LogLevel::Debug]);
+        assert!(interpreter.logs_as_strings()[0].ends_with(" invoke [2]"));
+
+        let scratch = interpreter.memory().account_snapshot(SCRATCH).unwrap();
+        assert_eq!(scratch[72..80], 42u64.to_le_bytes());
+        assert_eq!(scratch[80..112], CALLEE_ID);
+    }
+
+    #[test]
+    fn test_cpi_write_reaches_only_writable_accounts() {
+        let (interpreter, result) = privilege_test((true, false), (true, false));
+        assert_eq!(result.unwrap().exit_code, 0);
+        assert_eq!(interpreter.memory().account_snapshot(TARGET).unwrap(), 1u64.to_le_bytes());
+
+        let (interpreter, result) = privilege_test((true, false), (false, false));
+        assert!(matches!(
+            result.unwrap_err(),
+            TranspilerError::MemoryError(MemoryError { kind: MemoryErrorKind::WriteToReadOnly, .. })
+        ));
+        assert_eq!(interpreter.memory().account_snapshot(TARGET).unwrap(), [0; 8]);
+    }
+
+    #[test]
+    fn test_cpi_rejects_privilege_escalation() {
+        for (held, requested, escalated) in [
+            ((false, false), (true, false), "writable"),
+            ((true, false), (true, true), "signer"),
+        ] {
+            let (_, result) = privilege_test(held, requested);
+            assert!(matches!(
+                result.unwrap_err(),
+                TranspilerError::InterpreterError(InterpreterError::CpiPrivilegeEscalation { privilege, .. })
+                    if privilege == escalated
+            ));
+        }
+        assert!(privilege_test((true, true), (true, true)).1.is_ok());
+    }
+
+    #[test]
+    fn test_recursive_cpi_hits_depth_limit() {
+        let recursive = [lddw(1, SCRATCH), call(SOL_INVOKE_SIGNED_C), EXIT.to_vec()].concat();
+        let mut registry = ProgramRegistry::new();
+        registry.load(CALLER_ID, recursive).unwrap();
+        // Each level passes the scratch account on, so the callee can read the descriptor
+        let registry = Rc::new(registry);
+        let scratch = AccountMeta { pubkey: [3; 32], address: SCRATCH, is_writable: true, is_signer: false };
+        let mut interpreter =
+            BpfInterpreter::with_syscalls(Box::new(CpiSyscalls::with_accounts(Rc::clone(&registry), vec![scratch])));
+        let descriptor = scratch_with_meta(CALLER_ID, scratch.pubkey, true, false);
+        interpreter.memory_mut().map_account(SCRATCH, descriptor, true);
+
+        let error = registry.execute_instruction(&CALLER_ID, &mut interpreter).unwrap_err();
+        assert!(matches!(
+            error,
+            TranspilerError::InterpreterError(InterpreterError::CpiDepthExceeded { max_depth: MAX_CPI_DEPTH })
+        ));
+    }
+}
@@ -0,0 +1,612 @@
+use crate::error::{BpfParseError, TranspilerError};
+use crate::core_bpf::{decode_slot, BpfOpcode};
+use crate::memory::MM_PROGRAM_START;
+use crate::syscalls::{syscall_hash, syscall_name};
+use crate::wire;
+
+/// ELF file identification bytes
+pub const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+const ELF_HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: usize = 64;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+/// Section type of relocation tables without addends
+const SHT_REL: u32 = 9;
+const REL_ENTRY_SIZE: usize = 16;
+const SYMBOL_ENTRY_SIZE: usize = 24;
+/// Absolute reference to a symbol
+const R_BPF_64_64: u32 = 1;
+/// Reference relative to the program load address
+const R_BPF_64_RELATIVE: u32 = 8;
+/// 32-bit call target, used for dynamically linked syscalls
+const R_BPF_64_32: u32 = 10;
+/// Machine type of upstream eBPF objects
+pub const EM_BPF: u16 = 247;
+/// Machine type of Solana SBF objects
+pub const EM_SBF: u16 = 263;
+/// `UpgradeableLoaderState::ProgramData` variant tag
+const PROGRAMDATA_STATE: u32 = 3;
+/// Variant tag, slot, and an authority option with room for a pubkey
+pub const PROGRAMDATA_METADATA_SIZE: usize = 4 + 8 + 1 + 32;
+
+/// Whether `bytes` start with the ELF magic
+pub fn is_elf(bytes: &[u8]) -> bool {
+    bytes.starts_with(ELF_MAGIC)
+}
+
+/// A section of a loaded ELF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfSection<'a> {
+    pub name: String,
+    pub section_type: u32,
+    pub addr: u64,
+    pub offset: usize,
+    pub link: u32,
+    pub data: &'a [u8],
+}
+
+/// A 64-bit little-endian ELF file, as produced for Solana programs
+#[derive(Debug, Clone)]
+pub struct ElfFile<'a> {
+    bytes: &'a [u8],
+    sections: Vec<ElfSection<'a>>,
+}
+
+impl<'a> ElfFile<'a> {
+    /// Parse the ELF header and section table
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, TranspilerError> {
+        if bytes.len() < ELF_HEADER_SIZE || !is_elf(bytes) {
+            return Err(invalid_elf("missing ELF header".to_string()));
+        }
+        if bytes[4] != ELFCLASS64 || bytes[5] != ELFDATA2LSB {
+            return Err(invalid_elf("only 64-bit little-endian ELF files are supported".to_string()));
+        }
+
+        let section_table = read_u64(bytes, 0x28)? as usize;
+        let section_count = read_u16(bytes, 0x3c)? as usize;
+        let names_index = read_u16(bytes, 0x3e)? as usize;
+
+        let mut headers = Vec::with_capacity(section_count);
+        for index in 0..section_count {
+            let header = index
+                .checked_mul(SECTION_HEADER_SIZE)
+                .and_then(|offset| offset.checked_add(section_table))
+                .and_then(|start| bytes.get(start..start.checked_add(SECTION_HEADER_SIZE)?))
+                .ok_or_else(|| invalid_elf("section table out of bounds".to_string()))?;
+            let offset = read_u64(header, 24)? as usize;
+            let size = read_u64(header, 32)? as usize;
+            let data = offset
+                .checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or_else(|| invalid_elf(format!("section {} out of bounds", index)))?;
+            let section = ElfSection {
+                name: String::new(),
+                section_type: read_u32(header, 4)?,
+                addr: read_u64(header, 16)?,
+                offset,
+                link: read_u32(header, 40)?,
+                data,
+            };
+            headers.push((read_u32(header, 0)? as usize, section));
+        }
+
+        let names = headers.get(names_index).map_or(&[][..], |(_, section)| section.data);
+        let sections = headers
+            .into_iter()
+            .map(|(name_offset, section)| ElfSection { name: read_name(names, name_offset), ..section })
+            .collect();
+
+        Ok(Self { bytes, sections })
+    }
+
+    /// Raw file contents
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// All sections in file order
+    pub fn sections(&self) -> &[ElfSection<'a>] {
+        &self.sections
+    }
+
+    /// First section with the given name
+    pub fn section(&self, name: &str) -> Option<&ElfSection<'a>> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Target machine from the ELF header
+    pub fn machine(&self) -> u16 {
+        u16::from_le_bytes([self.bytes[0x12], self.bytes[0x13]])
+    }
+
+    /// Entry point virtual address from the ELF header
+    pub fn entry(&self) -> u64 {
+        u64::from_le_bytes(self.bytes[0x18..0x20].try_into().unwrap())
+    }
+
+    /// Byte offset of the entry point within `.text`
+    pub fn entrypoint_offset(&self) -> Result<u64, TranspilerError> {
+        let text = self
+            .section(".text")
+            .ok_or_else(|| invalid_elf("missing .text section".to_string()))?;
+        self.entry()
+            .checked_sub(text.addr)
+            .filter(|offset| *offset < text.data.len() as u64 && offset % 8 == 0)
+            .ok_or_else(|| invalid_elf(format!("entry point {:#x} is not an instruction in .text", self.entry())))
+    }
+
+    /// Name of the `.dynsym` symbol at `index`
+    fn dynamic_symbol_name(&self, index: usize) -> Result<String, TranspilerError> {
+        let symbols = self
+            .section(".dynsym")
+            .ok_or_else(|| invalid_elf("relocation references a symbol but .dynsym is missing".to_string()))?;
+        let names = self.sections.get(symbols.link as usize).map_or(&[][..], |section| section.data);
+        let name_offset = read_u32(symbols.data, index * SYMBOL_ENTRY_SIZE)? as usize;
+        Ok(read_name(names, name_offset))
+    }
+
+    /// Contents of the `.text` section
+    pub fn text(&self) -> Result<&'a [u8], TranspilerError> {
+        self.section(".text")
+            .map(|section| section.data)
+            .ok_or_else(|| invalid_elf("missing .text section".to_string()))
+    }
+}
+
+/// An ELF program with relocations applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedElf {
+    /// Relocated `.text` instructions
+    pub text: Vec<u8>,
+    /// Relocated file image, mapped read-only at `MM_PROGRAM_START`
+    pub program_image: Vec<u8>,
+    /// Byte offset of the entry point within `text`
+    pub entry_offset: usize,
+}
+
+impl ElfFile<'_> {
+    /// Apply `R_BPF_64_64`/`R_BPF_64_RELATIVE`/`R_BPF_64_32` relocations and split out `.text`
+    ///
+    /// Section virtual addresses are taken to equal file offsets, so a
+    /// relocated `LD_IMM64` pointing into `.rodata` resolves to the same
+    /// bytes once the image is mapped at `MM_PROGRAM_START`. `R_BPF_64_32`
+    /// call sites get the hash of their symbol's name, as syscalls expect.
+    pub fn load(&self) -> Result<LoadedElf, TranspilerError> {
+        let text = self
+            .section(".text")
+            .ok_or_else(|| invalid_elf("missing .text section".to_string()))?;
+        let text_range = text.offset..text.offset + text.data.len();
+        let entry_offset = self.entrypoint_offset()? as usize;
+        let symbols = self.section(".dynsym").map_or(&[][..], |section| section.data);
+
+        let mut image = self.bytes.to_vec();
+        for section in self.sections.iter().filter(|section| section.section_type == SHT_REL) {
+            for entry in section.data.chunks_exact(REL_ENTRY_SIZE) {
+                let offset = u64::from_le_bytes(entry[..8].try_into().unwrap()) as usize;
+                let info = u64::from_le_bytes(entry[8..].try_into().unwrap());
+                let addend = match info as u32 {
+                    R_BPF_64_64 => {
+                        let symbol = (info >> 32) as usize * SYMBOL_ENTRY_SIZE;
+                        read_u64(symbols, symbol + 8)?
+                    }
+                    R_BPF_64_RELATIVE => 0,
+                    R_BPF_64_32 => {
+                        let hash = syscall_hash(&self.dynamic_symbol_name((info >> 32) as usize)?) as u32;
+                        offset
+                            .checked_add(4)
+                            .and_then(|start| image.get_mut(start..start.checked_add(4)?))
+                            .ok_or_else(|| truncated(offset))?
+                            .copy_from_slice(&hash.to_le_bytes());
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                if text_range.contains(&offset) {
+                    let low = read_u32(&image, offset + 4)? as u64;
+                    let high = read_u32(&image, offset + 12)? as u64;
+                    let address = program_address(addend.wrapping_add(low | high << 32));
+                    image[offset + 4..offset + 8].copy_from_slice(&(address as u32).to_le_bytes());
+                    image[offset + 12..offset + 16].copy_from_slice(&((address >> 32) as u32).to_le_bytes());
+                } else {
+                    let address = program_address(addend.wrapping_add(read_u64(&image, offset)?));
+                    image[offset..offset + 8].copy_from_slice(&address.to_le_bytes());
+                }
+            }
+        }
+
+        Ok(LoadedElf {
+            text: image[text_range].to_vec(),
+            program_image: image,
+            entry_offset,
+        })
+    }
+}
+
+/// Summary of an ELF file accepted by [`verify_solana_bpf`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BpfElfInfo {
+    /// Byte offset of the entry point within `.text`
+    pub entrypoint_offset: u64,
+    /// Size of `.text` in bytes
+    pub text_size: usize,
+    /// Syscalls called from `.text`, sorted and deduplicated
+    pub syscalls: Vec<String>,
+}
+
+/// Check that `elf` looks like a Solana BPF program before loading it
+///
+/// Rejects ELF files for other machines, without `.text`, whose entry
+/// point lies outside `.text`, or with relocation types the loader does not
+/// apply. Syscalls are collected from `R_BPF_64_32` relocations and from
+/// calls already hashed into the code; unrecognized hashes are listed in hex.
+pub fn verify_solana_bpf(elf: &[u8]) -> Result<BpfElfInfo, TranspilerError> {
+    let file = ElfFile::parse(elf)?;
+    let machine = file.machine();
+    if machine != EM_BPF && machine != EM_SBF {
+        return Err(TranspilerError::BpfParseError(BpfParseError::UnsupportedMachine { machine }));
+    }
+
+    let entrypoint_offset = file.entrypoint_offset()?;
+    let text = file.section(".text").expect("checked by entrypoint_offset");
+
+    let mut syscalls = Vec::new();
+    for section in file.sections().iter().filter(|section| section.section_type == SHT_REL) {
+        for entry in section.data.chunks_exact(REL_ENTRY_SIZE) {
+            let info = u64::from_le_bytes(entry[8..].try_into().unwrap());
+            match info as u32 {
+                R_BPF_64_64 | R_BPF_64_RELATIVE => {}
+                R_BPF_64_32 => syscalls.push(file.dynamic_symbol_name((info >> 32) as usize)?),
+                relocation => return Err(invalid_elf(format!("unsupported relocation type {}", relocation))),
+            }
+        }
+    }
+
+    for slot in text.data.chunks_exact(8) {
+        let instruction = decode_slot(slot.try_into().unwrap());
+        // `call -1` is a placeholder patched through an `R_BPF_64_32` relocation
+        if instruction.opcode == BpfOpcode::Call && instruction.src_reg == 0 && instruction.immediate != -1 {
+            let id = instruction.immediate as u32 as u64;
+            syscalls.push(syscall_name(id).map_or_else(|| format!("{:#010x}", id), str::to_string));
+        }
+    }
+    syscalls.sort();
+    syscalls.dedup();
+
+    Ok(BpfElfInfo {
+        entrypoint_offset,
+        text_size: text.data.len(),
+        syscalls,
+    })
+}
+
+/// Header of an upgradeable program's ProgramData account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramMetadata {
+    /// Slot the program was last deployed at
+    pub slot: u64,
+    pub upgrade_authority: Option<[u8; 32]>,
+}
+
+/// Split a ProgramData account into its metadata and the ELF that follows
+///
+/// The header always takes [`PROGRAMDATA_METADATA_SIZE`] bytes, whether or
+/// not an upgrade authority is set.
+pub fn parse_programdata(account_data: &[u8]) -> Result<(ProgramMetadata, &[u8]), TranspilerError> {
+    if account_data.len() < PROGRAMDATA_METADATA_SIZE {
+        return Err(invalid_elf(format!(
+            "ProgramData account of {} bytes is shorter than its header",
+            account_data.len()
+        )));
+    }
+    let state = read_u32(account_data, 0)?;
+    if state != PROGRAMDATA_STATE {
+        return Err(invalid_elf(format!("loader state {} is not ProgramData", state)));
+    }
+    let upgrade_authority = match account_data[12] {
+        0 => None,
+        1 => Some(account_data[13..PROGRAMDATA_METADATA_SIZE].try_into().unwrap()),
+        tag => return Err(invalid_elf(format!("invalid upgrade authority tag {}", tag))),
+    };
+    let metadata = ProgramMetadata { slot: read_u64(account_data, 4)?, upgrade_authority };
+    Ok((metadata, &account_data[PROGRAMDATA_METADATA_SIZE..]))
+}
+
+/// ELF bytes of a ProgramData account, without the loader's header
+pub fn strip_programdata_header(account_data: &[u8]) -> Result<&[u8], TranspilerError> {
+    parse_programdata(account_data).map(|(_, elf)| elf)
+}
+
+/// Load an ELF file, applying relocations
+pub fn load_elf(bytes: &[u8]) -> Result<LoadedElf, TranspilerError> {
+    ElfFile::parse(bytes)?.load()
+}
+
+/// Extract the `.text` section from an ELF file
+pub fn extract_text_section(bytes: &[u8]) -> Result<Vec<u8>, TranspilerError> {
+    Ok(ElfFile::parse(bytes)?.text()?.to_vec())
+}
+
+fn program_address(address: u64) -> u64 {
+    if address < MM_PROGRAM_START {
+        address + MM_PROGRAM_START
+    } else {
+        address
+    }
+}
+
+fn read_name(names: &[u8], offset: usize) -> String {
+    let name = names.get(offset..).unwrap_or_default();
+    let end = name.iter().position(|byte| *byte == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..end]).into_owned()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, TranspilerError> {
+    wire::read_u16_le(bytes, offset).ok_or_else(|| truncated(offset))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TranspilerError> {
+    wire::read_u32_le(bytes, offset).ok_or_else(|| truncated(offset))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, TranspilerError> {
+    wire::read_u64_le(bytes, offset).ok_or_else(|| truncated(offset))
+}
+
+fn truncated(offset: usize) -> TranspilerError {
+    invalid_elf(format!("truncated at offset {}", offset))
+}
+
+fn invalid_elf(message: String) -> TranspilerError {
+    TranspilerError::BpfParseError(BpfParseError::InvalidElf { message })
+}
+
+/// Build a minimal ELF file with the given `(name, type, data)` sections
+#[cfg(test)]
+pub(crate) fn build_test_elf(sections: &[(&str, u32, &[u8])]) -> Vec<u8> {
+    let mut names = vec![0u8];
+    let mut name_offsets = Vec::new();
+    for (name, _, _) in sections.iter().chain([(".shstrtab", 3, &[][..])].iter()) {
+        name_offsets.push(names.len() as u32);
+        names.extend_from_slice(name.as_bytes());
+        names.push(0);
+    }
+
+    let mut bytes = vec![0u8; ELF_HEADER_SIZE];
+    bytes[..4].copy_from_slice(ELF_MAGIC);
+    bytes[4] = ELFCLASS64;
+    bytes[5] = ELFDATA2LSB;
+    bytes[6] = 1;
+    bytes[0x12..0x14].copy_from_slice(&247u16.to_le_bytes());
+
+    // `.dynsym` names its symbols in `.dynstr`; index 0 is the null section
+    let dynstr = sections.iter().position(|(name, _, _)| *name == ".dynstr").map_or(0, |index| index as u32 + 1);
+    let mut placed = Vec::new();
+    for (name, section_type, data) in sections.iter().chain([("", 3, names.as_slice())].iter()) {
+        if *name == ".text" {
+            let entry = bytes.len() as u64;
+            bytes[0x18..0x20].copy_from_slice(&entry.to_le_bytes());
+        }
+        let link = if *name == ".dynsym" { dynstr } else { 0 };
+        placed.push((*section_type, bytes.len() as u64, data.len() as u64, link));
+        bytes.extend_from_slice(data);
+    }
+
+    let section_table = bytes.len() as u64;
+    bytes.extend_from_slice(&[0u8; SECTION_HEADER_SIZE]);
+    for ((section_type, offset, size, link), name) in placed.into_iter().zip(name_offsets) {
+        let mut header = [0u8; SECTION_HEADER_SIZE];
+        header[..4].copy_from_slice(&name.to_le_bytes());
+        header[4..8].copy_from_slice(&section_type.to_le_bytes());
+        header[16..24].copy_from_slice(&offset.to_le_bytes());
+        header[24..32].copy_from_slice(&offset.to_le_bytes());
+        header[32..40].copy_from_slice(&size.to_le_bytes());
+        header[40..44].copy_from_slice(&link.to_le_bytes());
+        bytes.extend_from_slice(&header);
+    }
+
+    let section_count = sections.len() as u16 + 2;
+    bytes[0x28..0x30].copy_from_slice(&section_table.to_le_bytes());
+    bytes[0x3a..0x3c].copy_from_slice(&(SECTION_HEADER_SIZE as u16).to_le_bytes());
+    bytes[0x3c..0x3e].copy_from_slice(&section_count.to_le_bytes());
+    bytes[0x3e..0x40].copy_from_slice(&(section_count - 1).to_le_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InputFormat;
+    use crate::BpfZiskExecutor;
+
+    const PROGRAM: [u8; 16] = [
+        0xb7, 0x00, 0x00, 0x00, 0x2a, 0x00, 0x00, 0x00, // MOV64_IMM R0, 42
+        0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // EXIT
+    ];
+
+    #[test]
+    fn test_extract_text_section() {
+        let elf = build_test_elf(&[(".rodata", 1, b"hello"), (".text", 1, &PROGRAM)]);
+        let file = ElfFile::parse(&elf).unwrap();
+
+        assert_eq!(file.text().unwrap(), &PROGRAM);
+        assert_eq!(file.section(".rodata").unwrap().data, b"hello");
+        assert!(extract_text_section(&elf[..40]).is_err());
+
+        // A section table offset near usize::MAX must fail cleanly, not wrap
+        for hostile in [u64::MAX, u64::MAX - 24, u64::MAX - SECTION_HEADER_SIZE as u64 + 1] {
+            let mut bytes = elf.clone();
+            bytes[0x28..0x30].copy_from_slice(&hostile.to_le_bytes());
+            assert!(ElfFile::parse(&bytes).unwrap_err().to_string().contains("section table out of bounds"));
+        }
+        assert!(extract_text_section(&build_test_elf(&[(".data", 1, &PROGRAM)])).is_err());
+    }
+
+    fn rel(offset: usize, info: u64) -> Vec<u8> {
+        let mut entry = (offset as u64).to_le_bytes().to_vec();
+        entry.extend_from_slice(&info.to_le_bytes());
+        entry
+    }
+
+    /// `lddw r1, imm; mov r2, 5; call sol_log_; mov r0, 0; exit`
+    fn log_rodata_program(imm: u32) -> Vec<u8> {
+        let mut text = vec![0x18, 0x01, 0, 0];
+        text.extend_from_slice(&imm.to_le_bytes());
+        text.extend_from_slice(&[0; 8]);
+        text.extend_from_slice(&[0xb7, 0x02, 0, 0, 5, 0, 0, 0]);
+        text.extend_from_slice(&[0x85, 0, 0, 0, 0xbd, 0x59, 0x75, 0x20]);
+        text.extend_from_slice(&[0xb7, 0, 0, 0, 0, 0, 0, 0]);
+        text.extend_from_slice(&[0x95, 0, 0, 0, 0, 0, 0, 0]);
+        text
+    }
+
+    #[test]
+    fn test_rodata_relocations_resolve_at_runtime() {
+        // Sections are laid out after the 64-byte header in order
+        let rodata_offset = 64;
+        let text_offset = rodata_offset + 8;
+        let rodata = b"hello\0\0\0";
+
+        let relative = build_test_elf(&[
+            (".rodata", 1, rodata),
+            (".text", 1, &log_rodata_program(rodata_offset as u32)),
+            (".rel.dyn", SHT_REL, &rel(text_offset, R_BPF_64_RELATIVE as u64)),
+        ]);
+        let mut symbol = vec![0u8; 8];
+        symbol.extend_from_slice(&(rodata_offset as u64).to_le_bytes());
+        symbol.extend_from_slice(&[0; 8]);
+        let symbolic = build_test_elf(&[
+            (".rodata", 1, rodata),
+            (".text", 1, &log_rodata_program(0)),
+            (".rel.dyn", SHT_REL, &rel(text_offset, 1 << 32 | R_BPF_64_64 as u64)),
+            (".dynsym", 11, &[vec![0; SYMBOL_ENTRY_SIZE], symbol].concat()),
+        ]);
+
+        for elf in [relative, symbolic] {
+            let loaded = load_elf(&elf).unwrap();
+            assert_eq!(&loaded.text[4..8], &((MM_PROGRAM_START as u32 + rodata_offset as u32).to_le_bytes()));
+            assert_eq!(&loaded.text[12..16], &1u32.to_le_bytes());
+
+            let mut interpreter = crate::BpfInterpreter::new();
+            interpreter.memory_mut().map_program(loaded.program_image);
+            let program = crate::BpfParser::new().parse(&loaded.text).unwrap();
+            interpreter.execute(&program).unwrap();
+            assert_eq!(interpreter.logs_as_strings(), ["Program log: hello"]);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_syscalls_are_patched_with_their_hash() {
+        let rodata_offset = 64;
+        let text_offset = rodata_offset + 8;
+        let mut text = log_rodata_program(rodata_offset as u32);
+        // `call -1`, resolved through the relocation below
+        text[28..32].copy_from_slice(&[0xff; 4]);
+        let mut symbol = 1u32.to_le_bytes().to_vec();
+        symbol.resize(SYMBOL_ENTRY_SIZE, 0);
+        let elf = build_test_elf(&[
+            (".rodata", 1, b"hello\0\0\0"),
+            (".text", 1, &text),
+            (
+                ".rel.dyn",
+                SHT_REL,
+                &[rel(text_offset, R_BPF_64_RELATIVE as u64), rel(text_offset + 24, 1 << 32 | R_BPF_64_32 as u64)]
+                    .concat(),
+            ),
+            (".dynsym", 11, &[vec![0; SYMBOL_ENTRY_SIZE], symbol].concat()),
+            (".dynstr", 3, b"\0sol_log_\0"),
+        ]);
+        assert_eq!(verify_solana_bpf(&elf).unwrap().syscalls, ["sol_log_"]);
+
+        let loaded = load_elf(&elf).unwrap();
+        assert_eq!(&loaded.text[28..32], &(syscall_hash("sol_log_") as u32).to_le_bytes());
+
+        let mut interpreter = crate::BpfInterpreter::new();
+        interpreter.memory_mut().map_program(loaded.program_image);
+        let program = crate::BpfParser::new().parse(&loaded.text).unwrap();
+        interpreter.execute(&program).unwrap();
+        assert_eq!(interpreter.logs_as_strings(), ["Program log: hello"]);
+    }
+
+    #[test]
+    fn test_verify_solana_bpf() {
+        let elf = build_test_elf(&[(".rodata", 1, b"hello\0\0\0"), (".text", 1, &log_rodata_program(64))]);
+        let info = verify_solana_bpf(&elf).unwrap();
+        assert_eq!(info.entrypoint_offset, 0);
+        assert_eq!(info.text_size, 48);
+        assert_eq!(info.syscalls, ["sol_log_"]);
+
+        let mut x86 = elf.clone();
+        x86[0x12..0x14].copy_from_slice(&62u16.to_le_bytes());
+        assert!(matches!(
+            verify_solana_bpf(&x86),
+            Err(TranspilerError::BpfParseError(BpfParseError::UnsupportedMachine { machine: 62 }))
+        ));
+
+        assert!(verify_solana_bpf(&build_test_elf(&[(".data", 1, &PROGRAM)])).is_err());
+        let unsupported = build_test_elf(&[(".text", 1, &PROGRAM), (".rel.dyn", SHT_REL, &rel(64, 3))]);
+        assert!(verify_solana_bpf(&unsupported).unwrap_err().to_string().contains("relocation type 3"));
+    }
+
+    #[test]
+    fn test_programdata_header_is_stripped() {
+        let elf = build_test_elf(&[(".text", 1, &PROGRAM)]);
+        let mut account = 3u32.to_le_bytes().to_vec();
+        account.extend_from_slice(&1234u64.to_le_bytes());
+        account.push(1);
+        account.extend_from_slice(&[7; 32]);
+        account.extend_from_slice(&elf);
+
+        let (metadata, stripped) = parse_programdata(&account).unwrap();
+        assert_eq!(metadata, ProgramMetadata { slot: 1234, upgrade_authority: Some([7; 32]) });
+        assert_eq!(stripped, elf.as_slice());
+        assert_eq!(load_elf(stripped).unwrap().text, PROGRAM);
+
+        // Without an authority the pubkey bytes are still reserved
+        account[12] = 0;
+        assert_eq!(parse_programdata(&account).unwrap().0.upgrade_authority, None);
+        assert_eq!(strip_programdata_header(&account).unwrap(), elf.as_slice());
+
+        assert!(strip_programdata_header(&elf).is_err());
+        assert!(strip_programdata_header(&account[..PROGRAMDATA_METADATA_SIZE - 1]).is_err());
+        account[12] = 2;
+        assert!(strip_programdata_header(&account).is_err());
+    }
+
+    #[test]
+    fn test_execution_starts_at_entrypoint() {
+        let text = [
+            vec![0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00], // MOV64_IMM R0, 1
+            vec![0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // EXIT
+            PROGRAM.to_vec(),
+        ]
+        .concat();
+        let mut elf = build_test_elf(&[(".text", 1, &text)]);
+        assert_eq!(BpfZiskExecutor::new().execute_native(&elf).unwrap().exit_code, 1);
+
+        elf[0x18..0x20].copy_from_slice(&(64 + 16u64).to_le_bytes());
+        assert_eq!(load_elf(&elf).unwrap().entry_offset, 16);
+        assert_eq!(BpfZiskExecutor::new().execute_native(&elf).unwrap().exit_code, 42);
+
+        let mut program = crate::BpfParser::new().parse(&text).unwrap();
+        assert!(program.set_entry_offset(12).is_err());
+        assert!(program.set_entry_offset(32).is_err());
+        program.set_entry_offset(16).unwrap();
+        assert_eq!(crate::BpfInterpreter::new().execute(&program).unwrap().exit_code, 42);
+    }
+
+    #[test]
+    fn test_input_formats_parse_identically() {
+        let executor = BpfZiskExecutor::new();
+        let elf = build_test_elf(&[(".text", 1, &PROGRAM)]);
+
+        let raw = executor.parse_bpf_with_format(&PROGRAM, InputFormat::RawBytecode).unwrap();
+        let from_elf = executor.parse_bpf_with_format(&elf, InputFormat::Elf).unwrap();
+        let detected = executor.parse_bpf(&elf).unwrap();
+
+        assert_eq!(raw.instructions, from_elf.instructions);
+        assert_eq!(raw.instructions, detected.instructions);
+        assert!(executor.parse_bpf_with_format(&PROGRAM, InputFormat::Elf).is_err());
+    }
+}
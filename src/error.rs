@@ -17,6 +17,15 @@ pub enum BpfParseError {
     
     #[error("Invalid instruction format at offset {offset}")]
     InvalidInstructionFormat { offset: usize },
+    
+    #[error("Invalid ELF file: {message}")]
+    InvalidElf { message: String },
+    
+    #[error("Unsupported ELF machine type {machine} (expected EM_BPF or EM_SBF)")]
+    UnsupportedMachine { machine: u16 },
+    
+    #[error("Invalid entrypoint offset {offset:#x}")]
+    InvalidEntrypoint { offset: usize },
 }
 
 /// BPF interpreter errors
@@ -31,11 +40,14 @@ pub enum InterpreterError {
     #[error("Division by zero")]
     DivisionByZero,
     
-    #[error("Unsupported opcode: {opcode}")]
-    UnsupportedOpcode { opcode: u8 },
+    #[error("at BPF instruction #{index} (byte {byte_offset:#x}): unsupported opcode {opcode:#04x}")]
+    UnsupportedOpcode { opcode: u8, index: usize, byte_offset: usize },
     
-    #[error("Execution limit exceeded (max: 100,000 instructions)")]
-    ExecutionLimitExceeded,
+    #[error("Execution limit exceeded (max: {limit} instructions)")]
+    ExecutionLimitExceeded { limit: usize },
+    
+    #[error("Compute budget exceeded (limit: {limit} units)")]
+    ComputeBudgetExceeded { limit: u64 },
     
     #[error("Invalid jump target: {target}")]
     InvalidJumpTarget { target: usize },
@@ -45,6 +57,129 @@ pub enum InterpreterError {
     
     #[error("Stack underflow")]
     StackUnderflow,
+    
+    #[error("Unknown syscall: {id:#x}")]
+    UnknownSyscall { id: u64 },
+    
+    #[error("Invalid memory configuration: {message}")]
+    InvalidMemoryConfig { message: String },
+    
+    #[error("Cross-program invocation depth exceeded (max: {max_depth})")]
+    CpiDepthExceeded { max_depth: usize },
+    
+    #[error("Cross-program invocation failed with exit code {exit_code}")]
+    CpiFailed { exit_code: u64 },
+    
+    #[error("Cross-program invocation escalates {privilege} privilege of account {account}")]
+    CpiPrivilegeEscalation { account: String, privilege: &'static str },
+    
+    #[error("Cross-program invocation references account {account} not available to the caller")]
+    CpiUnknownAccount { account: String },
+    
+    #[error("Return data too large: {len} bytes (max: {max})")]
+    ReturnDataTooLarge { len: u64, max: usize },
+}
+
+/// Kind of BPF memory fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryErrorKind {
+    /// The access starts inside a region but runs past its end
+    OutOfBounds,
+    /// The access targets a read-only region
+    WriteToReadOnly,
+    /// The access writes an account owned by another program
+    NotOwner,
+    /// No region is mapped at the address
+    Unmapped,
+    /// The address is not aligned to the access width
+    Misaligned,
+}
+
+/// BPF memory fault
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{kind:?} access at {addr:#x} (size: {size})")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryError {
+    pub addr: u64,
+    pub size: usize,
+    pub kind: MemoryErrorKind,
+}
+
+/// Register read before it is written on some path
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Register r{register} read before initialization at instruction {instruction}")]
+pub struct VerifierError {
+    pub instruction: usize,
+    pub register: u8,
+}
+
+/// `ProgramError::ArithmeticOverflow`, as the Solana runtime encodes it in a program's return value
+pub const ARITHMETIC_OVERFLOW: u64 = 24 << 32;
+/// Base of the exit codes given to VM faults with no `ProgramError` counterpart
+pub const VM_FAULT_BASE: u64 = 0xffff << 48;
+
+/// Why a program stopped, with a stable numeric code for each reason
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BpfExitReason {
+    /// The program reached `EXIT` with this value in r0
+    Ok(u64),
+    /// A load or store faulted
+    Fault(MemoryError),
+    DivByZero,
+    /// The compute budget or instruction limit ran out
+    ComputeExceeded,
+    InvalidOpcode(u8),
+    /// Stack frames or cross-program invocations nested too deeply
+    CallDepthExceeded,
+}
+
+impl BpfExitReason {
+    /// Reason a program stopped with `error`, or `None` if the error is not a program fault
+    pub fn from_error(error: &TranspilerError) -> Option<Self> {
+        match error {
+            TranspilerError::MemoryError(fault) => Some(Self::Fault(fault.clone())),
+            TranspilerError::InterpreterError(error) => match error {
+                InterpreterError::MemoryAccessViolation { address, size, .. } => Some(Self::Fault(MemoryError {
+                    addr: *address as u64,
+                    size: *size,
+                    kind: MemoryErrorKind::OutOfBounds,
+                })),
+                InterpreterError::DivisionByZero => Some(Self::DivByZero),
+                InterpreterError::ComputeBudgetExceeded { .. } | InterpreterError::ExecutionLimitExceeded { .. } => {
+                    Some(Self::ComputeExceeded)
+                }
+                InterpreterError::UnsupportedOpcode { opcode, .. } => Some(Self::InvalidOpcode(*opcode)),
+                InterpreterError::StackOverflow | InterpreterError::CpiDepthExceeded { .. } => {
+                    Some(Self::CallDepthExceeded)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Exit code for this reason
+    ///
+    /// `Ok` passes r0 through and `DivByZero` is [`ARITHMETIC_OVERFLOW`];
+    /// the remaining faults count up from [`VM_FAULT_BASE`].
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::Ok(code) => *code,
+            Self::DivByZero => ARITHMETIC_OVERFLOW,
+            Self::Fault(_) => VM_FAULT_BASE + 1,
+            Self::ComputeExceeded => VM_FAULT_BASE + 2,
+            Self::InvalidOpcode(_) => VM_FAULT_BASE + 3,
+            Self::CallDepthExceeded => VM_FAULT_BASE + 4,
+        }
+    }
+}
+
+impl Default for BpfExitReason {
+    fn default() -> Self {
+        Self::Ok(0)
+    }
 }
 
 /// ZisK execution errors
@@ -64,6 +199,27 @@ pub enum ZiskExecutionError {
     
     #[error("Project initialization failed: {message}")]
     InitializationError { message: String },
+    
+    #[error("Invalid ZisK input: {message}")]
+    InvalidInput { message: String },
+    
+    #[error("Invalid witness data: {message}")]
+    InvalidWitness { message: String },
+    
+    #[error("Unsupported witness version: {version}")]
+    UnsupportedWitnessVersion { version: u16 },
+    
+    #[error("Invalid compute budget: {message}")]
+    InvalidComputeBudget { message: String },
+    
+    #[error("Unknown program: {program_id}")]
+    UnknownProgram { program_id: String },
+    
+    #[error("Invalid account: {message}")]
+    InvalidAccount { message: String },
+    
+    #[error("at BPF instruction #{index}: the ZisK guest cannot run `{instruction}`: {reason}")]
+    UnsupportedInGuest { index: usize, instruction: String, reason: &'static str },
 }
 
 /// Main transpiler error type
@@ -78,6 +234,9 @@ pub enum TranspilerError {
     #[error("ZisK execution error: {0}")]
     ZiskExecutionError(#[from] ZiskExecutionError),
     
+    #[error("Memory error: {0}")]
+    MemoryError(#[from] MemoryError),
+    
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
@@ -0,0 +1,94 @@
+//! Public outputs committed to the ZisK journal.
+//!
+//! ZisK exposes public outputs as numbered `u32` slots written with
+//! `set_output(id, value)`. The sink is passed in as a closure so the same
+//! layout code runs in the guest and in host-side tests.
+
+use crate::wire::{root_to_words_ordered, WordOrder};
+
+/// Commit `bytes` as little-endian `u32` words starting at output `start_id`
+///
+/// A partial trailing word is zero-padded. Returns the next free output id.
+pub fn commit_bytes(start_id: u32, bytes: &[u8], set_output: &mut impl FnMut(u32, u32)) -> u32 {
+    let mut id = start_id;
+    for chunk in bytes.chunks(4) {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        set_output(id, u32::from_le_bytes(word));
+        id += 1;
+    }
+    id
+}
+
+/// Commit a 32-byte hash as eight words in `order`, returning the next free id
+///
+/// Rebuild it with [`crate::wire::root_from_words_ordered`] and the same order.
+pub fn commit_root(start_id: u32, root: &[u8; 32], order: WordOrder, set_output: &mut impl FnMut(u32, u32)) -> u32 {
+    for (offset, word) in root_to_words_ordered(root, order).into_iter().enumerate() {
+        set_output(start_id + offset as u32, word);
+    }
+    start_id + 8
+}
+
+/// Commit the public outputs of one execution, returning the next free id
+///
+/// Layout: state root (8 words), inputs hash (8 words), return data length
+/// in bytes (1 word), then the padded return data. Both hashes use
+/// `order`; return data is always little-endian.
+pub fn commit_execution(
+    state_root: &[u8; 32],
+    inputs_hash: &[u8; 32],
+    return_data: &[u8],
+    order: WordOrder,
+    set_output: &mut impl FnMut(u32, u32),
+) -> u32 {
+    let mut id = commit_root(0, state_root, order, set_output);
+    id = commit_root(id, inputs_hash, order, set_output);
+    set_output(id, return_data.len() as u32);
+    commit_bytes(id + 1, return_data, set_output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_bytes_pads_tail() {
+        let mut outputs = Vec::new();
+        let bytes: Vec<u8> = (1..=33).collect();
+        let next = commit_bytes(3, &bytes, &mut |id, value| outputs.push((id, value)));
+
+        assert_eq!(next, 12);
+        assert_eq!(outputs.len(), 9);
+        assert_eq!(outputs[0], (3, u32::from_le_bytes([1, 2, 3, 4])));
+        assert_eq!(outputs[8], (11, 33));
+    }
+
+    #[test]
+    fn test_commit_execution_layout() {
+        let mut outputs = Vec::new();
+        let next = commit_execution(&[1; 32], &[2; 32], b"hello", WordOrder::Little, &mut |id, value| {
+            outputs.push((id, value))
+        });
+
+        assert_eq!(next, 19);
+        assert_eq!(outputs[7], (7, 0x01010101));
+        assert_eq!(outputs[8], (8, 0x02020202));
+        assert_eq!(outputs[16], (16, 5));
+        assert_eq!(outputs[18], (18, b'o' as u32));
+    }
+
+    #[test]
+    fn test_commit_root_order_round_trips() {
+        let root: [u8; 32] = core::array::from_fn(|i| i as u8);
+        for order in [WordOrder::Little, WordOrder::Big] {
+            let mut words = [0u32; 8];
+            assert_eq!(commit_root(4, &root, order, &mut |id, value| words[id as usize - 4] = value), 12);
+            assert_eq!(crate::wire::root_from_words_ordered(&words, order), root);
+        }
+
+        let mut first = 0;
+        commit_root(0, &root, WordOrder::Big, &mut |id, value| if id == 0 { first = value });
+        assert_eq!(first, 0x0001_0203);
+    }
+}
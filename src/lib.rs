@@ -8,6 +8,7 @@
 //! 1. **BPF Parser**: Parses BPF bytecode into structured instructions
 //! 2. **BPF Interpreter**: Executes BPF instructions natively in ZisK
 //! 3. **ZisK Integration**: Runs interpreter and generates cryptographic proofs
+//! 4. **Merkle**: Commits account state as a Merkle root with inclusion proofs
 //! 
 //! ## Benefits
 //! 
@@ -20,18 +21,58 @@ pub mod bpf_parser;
 pub mod bpf_interpreter;
 pub mod zisk_integration;
 pub mod types;
+pub mod core_bpf;
 pub mod error;
+pub mod merkle;
+pub mod zisk_input;
+pub mod syscalls;
+pub mod memory;
+pub mod witness;
+pub mod compute_budget;
+pub mod program_registry;
+pub mod elf_loader;
+pub mod sysvars;
+pub mod program_cache;
+pub mod wire;
+pub mod verifier;
+pub mod account;
+pub mod account_store;
+pub mod cpi;
+pub mod builder;
+pub mod journal;
+#[cfg(test)]
+mod testgen;
 
-pub use bpf_parser::BpfParser;
-pub use bpf_interpreter::BpfInterpreter;
-pub use zisk_integration::ZiskIntegration;
+pub use bpf_parser::{build_cfg, BasicBlock, BpfParser, Cfg, ParseOptions, UnknownPolicy};
+pub use bpf_interpreter::{BpfInterpreter, ExecutionOutcome, RunStatus, StepRecord, UnknownSyscallPolicy};
+pub use builder::BpfProgramBuilder;
+pub use core_bpf::OverflowKind;
+pub use zisk_integration::{ZiskIntegration, GUEST_COMPUTE_EXCEEDED, GUEST_DIVISION_BY_ZERO};
+pub use account::SolanaAccount;
+pub use account_store::AccountStore;
+pub use cpi::{AccountMeta, CpiSyscalls};
+pub use merkle::{AccountLeaf, CommitmentHasher, MerklePath, MerkleTree, Sha256Hasher};
+#[cfg(feature = "blake3")]
+pub use merkle::Blake3Hasher;
+pub use zisk_input::{InstructionInput, MultiProgramInput, ZiskInput};
+pub use memory::{BpfMemory, MemoryConfig, MemoryRegionType};
+pub use witness::{AccountChange, TraceStep, WitnessData};
+pub use compute_budget::{ComputeBudget, ComputeBudgetInstruction};
+pub use program_cache::ProgramCache;
+pub use program_registry::ProgramRegistry;
+pub use syscalls::{
+    syscall_hash, CostModel, DefaultSyscalls, LogEntry, LogLevel, ReturnData, SyscallContext, SyscallHandler,
+};
+pub use sysvars::{Clock, Rent, Sysvars};
 pub use types::*;
 pub use error::*;
 
 /// Main BPF interpreter for ZisK execution
 pub struct BpfZiskExecutor {
     parser: BpfParser,
-    interpreter: BpfInterpreter,
+    cache: ProgramCache,
+    trace: bool,
+    steps_csv: Option<std::path::PathBuf>,
 }
 
 impl BpfZiskExecutor {
@@ -39,14 +80,16 @@ impl BpfZiskExecutor {
     pub fn new() -> Self {
         Self {
             parser: BpfParser::new(),
-            interpreter: BpfInterpreter::new(),
+            cache: ProgramCache::new(),
+            trace: false,
+            steps_csv: None,
         }
     }
     
     /// Execute BPF program directly in ZisK
     pub fn execute_in_zisk(&mut self, bpf_bytecode: &[u8]) -> Result<ExecutionResult, TranspilerError> {
         // Parse BPF bytecode
-        let bpf_program = self.parser.parse(bpf_bytecode)?;
+        let bpf_program = self.parse_bpf(bpf_bytecode)?;
         
         // Execute in ZisK
         let mut zisk = ZiskIntegration::new();
@@ -57,7 +100,7 @@ impl BpfZiskExecutor {
     /// Execute BPF program and generate proof in ZisK
     pub fn execute_with_proof(&mut self, bpf_bytecode: &[u8]) -> Result<(ExecutionResult, Vec<u8>), TranspilerError> {
         // Parse BPF bytecode
-        let bpf_program = self.parser.parse(bpf_bytecode)?;
+        let bpf_program = self.parse_bpf(bpf_bytecode)?;
 
         // Execute and generate proof in ZisK
         let mut zisk = ZiskIntegration::new();
@@ -65,19 +108,157 @@ impl BpfZiskExecutor {
         zisk.execute_with_proof(&bpf_program)
     }
 
+    /// Print a line per executed instruction to stdout during native execution
+    pub fn set_trace(&mut self, enabled: bool) {
+        self.trace = enabled;
+    }
+
+    /// Write a CSV row per executed instruction to `path` during native execution
+    pub fn set_steps_csv(&mut self, path: Option<std::path::PathBuf>) {
+        self.steps_csv = path;
+    }
+
+    /// Execute BPF program with the native interpreter (no ZisK toolchain required)
+    ///
+    /// ELF input is relocated and its image mapped at `MM_PROGRAM_START` so
+    /// `.rodata` references resolve.
+    pub fn execute_native(&mut self, bpf_bytecode: &[u8]) -> Result<ExecutionResult, TranspilerError> {
+        self.run_native(bpf_bytecode, None)
+    }
+
+    /// Execute with `input_data` mapped read-only at `MM_INPUT_START`, pointed to by r1
+    pub fn execute_native_with_input(
+        &mut self,
+        bpf_bytecode: &[u8],
+        input_data: Vec<u8>,
+    ) -> Result<ExecutionResult, TranspilerError> {
+        self.run_native(bpf_bytecode, Some(input_data))
+    }
+
+    fn run_native(&mut self, bpf_bytecode: &[u8], input_data: Option<Vec<u8>>) -> Result<ExecutionResult, TranspilerError> {
+        let mut interpreter = BpfInterpreter::new();
+        if let Some(input_data) = input_data {
+            interpreter.memory_mut().map_input(input_data);
+        }
+        let (bpf_program, program_image) =
+            load_with_format(&self.parser, bpf_bytecode, InputFormat::detect(bpf_bytecode))?;
+        if let Some(image) = program_image {
+            interpreter.memory_mut().map_program(image);
+        }
+        interpreter.set_tracing(self.trace);
+        interpreter.set_step_recording(self.steps_csv.is_some());
+        let result = interpreter.execute(&bpf_program);
+        for line in interpreter.trace() {
+            println!("{}", line);
+        }
+        if let Some(path) = &self.steps_csv {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            interpreter.write_steps_csv(&mut file)?;
+        }
+        result
+    }
+
     /// Parse BPF bytecode without execution
+    ///
+    /// Input is treated as raw bytecode unless it starts with the ELF magic.
     pub fn parse_bpf(&self, bpf_bytecode: &[u8]) -> Result<BpfProgram, TranspilerError> {
+        self.parse_bpf_with_format(bpf_bytecode, InputFormat::detect(bpf_bytecode))
+    }
+
+    /// Parse raw BPF bytecode for inspection, without format detection or execution
+    pub fn parse_only(&self, bpf_bytecode: &[u8]) -> Result<BpfProgram, TranspilerError> {
         self.parser.parse(bpf_bytecode)
     }
+
+    /// Compare opcode support of the native interpreter and the ZisK guest for a program
+    pub fn report(&self, bpf_bytecode: &[u8]) -> Result<CompatReport, TranspilerError> {
+        let program = self.parse_bpf_with_format(bpf_bytecode, InputFormat::detect(bpf_bytecode))?;
+        let mut report = CompatReport::default();
+        for instruction in &program.instructions {
+            match report.opcodes.iter_mut().find(|support| support.opcode == instruction.opcode) {
+                Some(support) => support.count += 1,
+                None => report.opcodes.push(OpcodeSupport {
+                    opcode: instruction.opcode,
+                    count: 1,
+                    interpreter: BpfInterpreter::supports_opcode(instruction.opcode),
+                    zisk: ZiskIntegration::supports_opcode(instruction.opcode),
+                }),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Parse input of an explicit format without execution
+    pub fn parse_bpf_with_format(&self, bytes: &[u8], format: InputFormat) -> Result<BpfProgram, TranspilerError> {
+        parse_with_format(&self.parser, bytes, format)
+    }
+
+    /// Parse BPF bytecode, reusing the result for identical input
+    pub fn parse_cached(&mut self, bpf_bytecode: &[u8]) -> Result<&BpfProgram, TranspilerError> {
+        let parser = &self.parser;
+        self.cache
+            .get_or_parse(bpf_bytecode, |bytes| parse_with_format(parser, bytes, InputFormat::detect(bytes)))
+    }
+
+    /// Programs parsed by [`Self::parse_cached`]
+    pub fn program_cache(&self) -> &ProgramCache {
+        &self.cache
+    }
+}
+
+fn parse_with_format(parser: &BpfParser, bytes: &[u8], format: InputFormat) -> Result<BpfProgram, TranspilerError> {
+    Ok(load_with_format(parser, bytes, format)?.0)
+}
+
+/// Parse input of `format`, keeping an ELF's relocated image for mapping at `MM_PROGRAM_START`
+pub(crate) fn load_with_format(
+    parser: &BpfParser,
+    bytes: &[u8],
+    format: InputFormat,
+) -> Result<(BpfProgram, Option<Vec<u8>>), TranspilerError> {
+    match format {
+        InputFormat::RawBytecode => Ok((parser.parse(bytes)?, None)),
+        InputFormat::Elf => {
+            let loaded = elf_loader::load_elf(bytes)?;
+            let mut program = parser.parse(&loaded.text)?;
+            program.set_entry_offset(loaded.entry_offset)?;
+            Ok((program, Some(loaded.program_image)))
+        }
+    }
 }
 
 /// Result of BPF program execution
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature this (de)serializes; `execution_time` is
+/// skipped so serialized results are reproducible.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExecutionResult {
+    /// `exit_reason.code()`
     pub exit_code: u64,
+    pub exit_reason: BpfExitReason,
     pub registers: [u64; 11],
     pub instructions_executed: usize,
+    /// Compute units consumed; unlike `execution_time`, identical across runs
+    pub logical_time: u64,
+    /// Wall-clock time, for diagnostics only; never commit it to a proof
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub execution_time: std::time::Duration,
+    /// Executed instructions per opcode (empty unless profiling is enabled)
+    pub opcode_counts: std::collections::HashMap<u8, u64>,
+    /// Most-executed program counters, hottest first (empty unless profiling is enabled)
+    pub hottest_pcs: Vec<(usize, u64)>,
+    /// Program counters of wrapping add/sub/mul (empty unless overflow tracking is enabled)
+    pub overflow_events: Vec<(usize, core_bpf::OverflowKind)>,
+}
+
+impl ExecutionResult {
+    /// Opcode counts sorted by frequency, most frequent first
+    pub fn opcode_histogram(&self) -> Vec<(u8, u64)> {
+        let mut histogram: Vec<(u8, u64)> = self.opcode_counts.iter().map(|(opcode, count)| (*opcode, *count)).collect();
+        histogram.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        histogram
+    }
 }
 
 impl Default for BpfZiskExecutor {
@@ -85,3 +266,116 @@ impl Default for BpfZiskExecutor {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_only_returns_program() {
+        // MOV64_IMM R0, 6; MUL64_IMM R0, 7; ADD64_IMM R0, 1; EXIT
+        let math_program = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00,
+            0x27, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00,
+            0x07, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let program = BpfZiskExecutor::new().parse_only(&math_program).unwrap();
+        assert_eq!(program.instructions.len(), 4);
+        assert_eq!(program.instructions[0].opcode, BpfOpcode::Mov64Imm);
+        assert_eq!(program.instructions[0].immediate, 6);
+    }
+
+    #[test]
+    fn test_program_reads_instruction_data() {
+        // LDX8 R0, [R1+0]; EXIT
+        let program = vec![
+            0x71, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut executor = BpfZiskExecutor::new();
+        let result = executor.execute_native_with_input(&program, vec![7, 8, 9]).unwrap();
+        assert_eq!(result.exit_code, 7);
+        assert_eq!(result.registers[1], memory::MM_INPUT_START);
+        assert!(executor.execute_native(&program).is_err());
+    }
+
+    #[test]
+    fn test_logical_time_is_reproducible() {
+        let program = testgen::gen_program(3, 64);
+        let first = BpfZiskExecutor::new().execute_native(&program).unwrap();
+        let second = BpfZiskExecutor::new().execute_native(&program).unwrap();
+        assert!(first.logical_time > 0);
+        assert_eq!(first.logical_time, second.logical_time);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_execution_result_json_round_trip() {
+        let program = BpfProgramBuilder::new().mov64_imm(1, -1).mov64_imm(0, 42).exit().build();
+        let result = BpfZiskExecutor::new().execute_native(&program).unwrap();
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["registers"].as_array().unwrap().len(), 11);
+        assert_eq!(json["registers"][1], u64::MAX);
+        assert_eq!(json["logical_time"], 2);
+        assert!(json.get("execution_time").is_none());
+
+        let decoded: ExecutionResult = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.exit_code, 42);
+        assert_eq!(decoded.exit_reason, result.exit_reason);
+        assert_eq!(decoded.registers, result.registers);
+        assert_eq!(decoded.instructions_executed, result.instructions_executed);
+        assert_eq!(decoded.logical_time, result.logical_time);
+        assert_eq!(decoded.execution_time, std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_compat_report_splits_by_path() {
+        let program = BpfProgramBuilder::new()
+            .mov64_imm(0, 7)
+            .mov64_imm(1, 3)
+            .instruction(BpfOpcode::Xor64Reg, 0, 1, 0, 0)
+            .syscall(syscalls::SOL_LOG)
+            .exit()
+            .build();
+
+        let report = BpfZiskExecutor::new().report(&program).unwrap();
+        assert_eq!(report.opcodes.len(), 4);
+        assert_eq!(report.opcodes[0].count, 2);
+        assert_eq!(report.interpreter_only(), [BpfOpcode::Xor64Reg, BpfOpcode::Call]);
+        assert!(report.zisk_only().is_empty());
+        assert!(!report.fully_compatible());
+    }
+
+    #[test]
+    fn test_steps_csv_has_a_row_per_executed_instruction() {
+        // MOV64_IMM R1, 3; MOV64_IMM R0, 4; ADD64_REG R0, R1; EXIT
+        let program = vec![
+            0xb7, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+            0x0f, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let path = std::env::temp_dir().join("bpf_steps_csv_test.csv");
+
+        let mut executor = BpfZiskExecutor::new();
+        executor.set_steps_csv(Some(path.clone()));
+        let result = executor.execute_native(&program).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("pc,opcode,dst,src,imm,r0,"));
+        assert!(lines[0].ends_with(",r10,cu_used"));
+        assert_eq!(lines.len() - 1, result.instructions_executed);
+
+        let last: Vec<&str> = lines.last().unwrap().split(',').collect();
+        assert_eq!(&last[..5], &["2", "0x0f", "0", "1", "0"]);
+        let registers: Vec<u64> = last[5..16].iter().map(|value| value.parse().unwrap()).collect();
+        assert_eq!(registers, result.registers);
+        assert_eq!(last[16], "3");
+    }
+}
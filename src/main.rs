@@ -1,6 +1,59 @@
+use bpf_zisk_interpreter::zisk_input::load_zisk_input_file;
 use bpf_zisk_interpreter::BpfZiskExecutor;
+use std::path::{Path, PathBuf};
+
+/// Run a framed input file through the native interpreter and print a JSON summary
+///
+/// With `deterministic_time`, wall-clock time is left out so the summary
+/// is reproducible.
+fn run_input_file(path: &str, trace: bool, steps_csv: Option<&String>, deterministic_time: bool) {
+    let mut executor = BpfZiskExecutor::new();
+    executor.set_trace(trace);
+    executor.set_steps_csv(steps_csv.map(PathBuf::from));
+    let result = load_zisk_input_file(Path::new(path))
+        .and_then(|input| executor.execute_native_with_input(&input.program, input.input_data));
+
+    let summary = match result {
+        Ok(result) => {
+            let mut summary = serde_json::json!({
+                "success": true,
+                "exit_code": result.exit_code,
+                "instructions_executed": result.instructions_executed,
+                "logical_time": result.logical_time,
+                "registers": result.registers,
+            });
+            if !deterministic_time {
+                summary["execution_time_us"] = (result.execution_time.as_micros() as u64).into();
+            }
+            summary
+        }
+        Err(e) => serde_json::json!({
+            "success": false,
+            "error": e.to_string(),
+        }),
+    };
+    println!("{}", summary);
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(position) = args.iter().position(|arg| arg == "--input-file") {
+        match args.get(position + 1) {
+            Some(path) => {
+                let steps_csv = args
+                    .iter()
+                    .position(|arg| arg == "--emit-trace-file")
+                    .and_then(|position| args.get(position + 1));
+                let flag = |name: &str| args.iter().any(|arg| arg == name);
+                run_input_file(path, flag("--trace"), steps_csv, flag("--deterministic-time"))
+            }
+            None => eprintln!(
+                "Usage: bpf-interpreter --input-file <path> [--trace] [--emit-trace-file <csv>] [--deterministic-time]"
+            ),
+        }
+        return;
+    }
+
     println!("🚀 BPF Interpreter for ZisK Integration");
     println!("========================================\n");
 
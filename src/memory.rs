@@ -0,0 +1,465 @@
+use crate::error::{InterpreterError, MemoryError, MemoryErrorKind, TranspilerError};
+
+/// Start of the program (read-only data) region
+pub const MM_PROGRAM_START: u64 = 0x1_0000_0000;
+/// Start of the stack region
+pub const MM_STACK_START: u64 = 0x2_0000_0000;
+/// Start of the heap region
+pub const MM_HEAP_START: u64 = 0x3_0000_0000;
+/// Start of the input region
+pub const MM_INPUT_START: u64 = 0x4_0000_0000;
+
+/// Maximum heap size a program can request (Solana's `MAX_HEAP_FRAME_BYTES`)
+pub const MAX_HEAP_SIZE: usize = 256 * 1024;
+/// Size of a single stack frame
+pub const STACK_FRAME_SIZE: usize = 4096;
+
+/// Default heap size (Solana's default heap frame)
+pub const DEFAULT_HEAP_SIZE: usize = 32 * 1024;
+/// Default stack size (64 frames of 4KB)
+pub const DEFAULT_STACK_SIZE: usize = 64 * STACK_FRAME_SIZE;
+
+/// Heap and stack sizing for an interpreter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryConfig {
+    pub heap_size: usize,
+    pub stack_size: usize,
+}
+
+impl MemoryConfig {
+    /// Check the sizes against Solana's limits
+    pub fn validate(&self) -> Result<(), TranspilerError> {
+        if self.heap_size == 0 || self.heap_size > MAX_HEAP_SIZE {
+            return Err(invalid_config(format!(
+                "heap size {} must be between 1 and {} bytes",
+                self.heap_size, MAX_HEAP_SIZE
+            )));
+        }
+        if self.stack_size == 0 || !self.stack_size.is_multiple_of(STACK_FRAME_SIZE) {
+            return Err(invalid_config(format!(
+                "stack size {} must be a non-zero multiple of {} bytes",
+                self.stack_size, STACK_FRAME_SIZE
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            heap_size: DEFAULT_HEAP_SIZE,
+            stack_size: DEFAULT_STACK_SIZE,
+        }
+    }
+}
+
+/// Kind of a mapped memory region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryRegionType {
+    Program,
+    Stack,
+    Heap,
+    Input,
+    Account,
+}
+
+/// A contiguous region of BPF virtual memory
+#[derive(Debug, Clone)]
+pub struct MemoryRegion {
+    pub region_type: MemoryRegionType,
+    pub start: u64,
+    pub data: Vec<u8>,
+    pub writable: bool,
+    /// Program allowed to write the region, if writes are restricted to one
+    pub owner: Option<[u8; 32]>,
+}
+
+impl MemoryRegion {
+    /// One past the last virtual address of the region, saturating at `u64::MAX`
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.data.len() as u64)
+    }
+
+    fn contains(&self, address: u64, size: usize) -> bool {
+        address >= self.start
+            && address
+                .checked_add(size as u64)
+                .is_some_and(|end| end <= self.end())
+    }
+}
+
+/// BPF virtual memory made of non-overlapping regions
+///
+/// Stack and heap start zeroed and are zeroed again by [`BpfMemory::reset`],
+/// so a program never observes bytes left by an earlier run. Account and
+/// input regions hold whatever the caller maps.
+#[derive(Debug, Clone)]
+pub struct BpfMemory {
+    regions: Vec<MemoryRegion>,
+    program_id: [u8; 32],        // Executing program, checked against account owners
+    allow_unaligned: bool,
+    heap_used: u64,              // Bump pointer, as an offset into the heap
+}
+
+impl BpfMemory {
+    /// Create memory with zeroed heap and stack regions
+    pub fn new(heap_size: usize, stack_size: usize) -> Self {
+        Self {
+            regions: vec![
+                MemoryRegion {
+                    region_type: MemoryRegionType::Stack,
+                    start: MM_STACK_START,
+                    data: vec![0; stack_size],
+                    writable: true,
+                    owner: None,
+                },
+                MemoryRegion {
+                    region_type: MemoryRegionType::Heap,
+                    start: MM_HEAP_START,
+                    data: vec![0; heap_size],
+                    writable: true,
+                    owner: None,
+                },
+            ],
+            program_id: [0; 32],
+            allow_unaligned: false,
+            heap_used: 0,
+        }
+    }
+
+    /// Zero the stack and heap and release heap allocations; other mapped
+    /// regions are kept as-is
+    pub fn reset(&mut self) {
+        self.heap_used = 0;
+        for region in &mut self.regions {
+            if matches!(region.region_type, MemoryRegionType::Stack | MemoryRegionType::Heap) {
+                region.data.fill(0);
+            }
+        }
+    }
+
+    /// Bump-allocate `size` bytes aligned to `align` (a power of two) from the heap
+    ///
+    /// Returns `None` once the heap is exhausted, or if `align` is not a power
+    /// of two. Memory is only released by `reset`.
+    pub fn alloc(&mut self, size: u64, align: u64) -> Option<u64> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+        let heap_len = self.region_slice(MemoryRegionType::Heap)?.len() as u64;
+        let start = self.heap_used.checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(size).filter(|end| *end <= heap_len)?;
+        self.heap_used = end;
+        Some(MM_HEAP_START + start)
+    }
+
+    /// Permit 2/4/8-byte accesses at addresses not aligned to their width
+    pub fn set_allow_unaligned(&mut self, allow: bool) {
+        self.allow_unaligned = allow;
+    }
+
+    /// Whether unaligned multi-byte accesses are permitted
+    pub fn allow_unaligned(&self) -> bool {
+        self.allow_unaligned
+    }
+
+    /// Map a read-only program image at `MM_PROGRAM_START`, replacing any previous one
+    pub fn map_program(&mut self, image: Vec<u8>) {
+        self.regions.retain(|region| region.region_type != MemoryRegionType::Program);
+        self.regions.push(MemoryRegion {
+            region_type: MemoryRegionType::Program,
+            start: MM_PROGRAM_START,
+            data: image,
+            writable: false,
+            owner: None,
+        });
+    }
+
+    /// Map a read-only input buffer at `MM_INPUT_START`, replacing any previous one
+    pub fn map_input(&mut self, data: Vec<u8>) {
+        self.set_input(data, MM_INPUT_START);
+    }
+
+    /// Map a read-only input buffer at `base`, replacing any previous one
+    pub fn set_input(&mut self, data: Vec<u8>, base: u64) {
+        self.regions.retain(|region| region.region_type != MemoryRegionType::Input);
+        self.regions.push(MemoryRegion {
+            region_type: MemoryRegionType::Input,
+            start: base,
+            data,
+            writable: false,
+            owner: None,
+        });
+    }
+
+    /// Start of the mapped input region, if any
+    pub fn input_start(&self) -> Option<u64> {
+        self.regions
+            .iter()
+            .find(|region| region.region_type == MemoryRegionType::Input)
+            .map(|region| region.start)
+    }
+
+    /// Map an account's data at `virtual_addr`
+    pub fn map_account(&mut self, virtual_addr: u64, data: Vec<u8>, writable: bool) {
+        self.map_owned_account(virtual_addr, data, writable, None);
+    }
+
+    /// Map an account's data at `virtual_addr`, writable only by `owner` when one is given
+    pub fn map_owned_account(&mut self, virtual_addr: u64, data: Vec<u8>, writable: bool, owner: Option<[u8; 32]>) {
+        self.regions.push(MemoryRegion {
+            region_type: MemoryRegionType::Account,
+            start: virtual_addr,
+            data,
+            writable,
+            owner,
+        });
+    }
+
+    /// Owner of the account mapped at `virtual_addr`, if it has one
+    pub fn account_owner(&self, virtual_addr: u64) -> Option<[u8; 32]> {
+        self.regions
+            .iter()
+            .find(|region| region.region_type == MemoryRegionType::Account && region.start == virtual_addr)
+            .and_then(|region| region.owner)
+    }
+
+    /// Set the executing program; writes to accounts owned by any other program fault
+    pub fn set_program_id(&mut self, program_id: [u8; 32]) {
+        self.program_id = program_id;
+    }
+
+    /// All mapped regions
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions
+    }
+
+    /// Initial frame pointer (top of the stack)
+    pub fn stack_top(&self) -> u64 {
+        self.regions
+            .iter()
+            .find(|region| region.region_type == MemoryRegionType::Stack)
+            .map_or(MM_STACK_START, MemoryRegion::end)
+    }
+
+    /// Contents of the first region of the given type
+    pub fn region_slice(&self, region_type: MemoryRegionType) -> Option<&[u8]> {
+        self.regions
+            .iter()
+            .find(|region| region.region_type == region_type)
+            .map(|region| region.data.as_slice())
+    }
+
+    /// Copy of the account data mapped at `virtual_addr`
+    pub fn account_snapshot(&self, virtual_addr: u64) -> Option<Vec<u8>> {
+        self.regions
+            .iter()
+            .find(|region| region.region_type == MemoryRegionType::Account && region.start == virtual_addr)
+            .map(|region| region.data.clone())
+    }
+
+    /// Read memory at a virtual address
+    pub fn read_memory(&self, address: u64, size: usize) -> Result<&[u8], TranspilerError> {
+        self.check_alignment(address, size)?;
+        self.read_bytes(address, size)
+    }
+
+    /// Write memory at a virtual address
+    pub fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), TranspilerError> {
+        self.check_alignment(address, data.len())?;
+        self.write_bytes(address, data)
+    }
+
+    /// Read a byte range without alignment checks (syscall buffers)
+    pub fn read_bytes(&self, address: u64, size: usize) -> Result<&[u8], TranspilerError> {
+        let region = self
+            .regions
+            .iter()
+            .find(|region| region.contains(address, size))
+            .ok_or_else(|| self.fault(address, size, false))?;
+        let offset = (address - region.start) as usize;
+        Ok(&region.data[offset..offset + size])
+    }
+
+    /// Write a byte range without alignment checks (syscall buffers)
+    pub fn write_bytes(&mut self, address: u64, data: &[u8]) -> Result<(), TranspilerError> {
+        self.write_region(address, data, true)
+    }
+
+    /// Write a byte range on behalf of the runtime rather than the program,
+    /// skipping the ownership check (CPI account copy-back)
+    pub fn write_account_data(&mut self, address: u64, data: &[u8]) -> Result<(), TranspilerError> {
+        self.write_region(address, data, false)
+    }
+
+    fn write_region(&mut self, address: u64, data: &[u8], check_owner: bool) -> Result<(), TranspilerError> {
+        let index = self
+            .regions
+            .iter()
+            .position(|region| {
+                region.writable && (!check_owner || self.owns(region)) && region.contains(address, data.len())
+            })
+            .ok_or_else(|| self.fault(address, data.len(), true))?;
+        let region = &mut self.regions[index];
+        let offset = (address - region.start) as usize;
+        region.data[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Fault 2/4/8-byte accesses not aligned to their width, unless allowed
+    fn check_alignment(&self, address: u64, size: usize) -> Result<(), TranspilerError> {
+        if self.allow_unaligned || !matches!(size, 2 | 4 | 8) || address.is_multiple_of(size as u64) {
+            return Ok(());
+        }
+        Err(TranspilerError::MemoryError(MemoryError {
+            addr: address,
+            size,
+            kind: MemoryErrorKind::Misaligned,
+        }))
+    }
+
+    /// Whether the executing program may write `region`
+    fn owns(&self, region: &MemoryRegion) -> bool {
+        region.owner.is_none_or(|owner| owner == self.program_id)
+    }
+
+    /// Classify a failed access
+    fn fault(&self, address: u64, size: usize, write: bool) -> TranspilerError {
+        let wraps = address.checked_add(size as u64).is_none();
+        let kind = match self
+            .regions
+            .iter()
+            .find(|region| address >= region.start && address < region.end())
+        {
+            _ if wraps => MemoryErrorKind::OutOfBounds,
+            None => MemoryErrorKind::Unmapped,
+            Some(region) if write && !region.writable && region.contains(address, size) => {
+                MemoryErrorKind::WriteToReadOnly
+            }
+            Some(region) if write && !self.owns(region) && region.contains(address, size) => MemoryErrorKind::NotOwner,
+            Some(_) => MemoryErrorKind::OutOfBounds,
+        };
+        TranspilerError::MemoryError(MemoryError { addr: address, size, kind })
+    }
+}
+
+impl Default for BpfMemory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEAP_SIZE, DEFAULT_STACK_SIZE)
+    }
+}
+
+impl From<MemoryConfig> for BpfMemory {
+    fn from(config: MemoryConfig) -> Self {
+        Self::new(config.heap_size, config.stack_size)
+    }
+}
+
+fn invalid_config(message: String) -> TranspilerError {
+    TranspilerError::InterpreterError(InterpreterError::InvalidMemoryConfig { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_slice_and_account_snapshot() {
+        let mut memory = BpfMemory::new(64, 4096);
+        memory.map_account(MM_INPUT_START, vec![1, 2, 3], true);
+
+        memory.write_memory(MM_HEAP_START + 8, &[0xaa, 0xbb]).unwrap();
+        memory.write_memory(MM_INPUT_START + 1, &[9]).unwrap();
+
+        let heap = memory.region_slice(MemoryRegionType::Heap).unwrap();
+        assert_eq!(heap.len(), 64);
+        assert_eq!(&heap[8..10], &[0xaa, 0xbb]);
+        assert_eq!(memory.account_snapshot(MM_INPUT_START), Some(vec![1, 9, 3]));
+        assert_eq!(memory.account_snapshot(MM_INPUT_START + 1), None);
+        assert!(memory.region_slice(MemoryRegionType::Program).is_none());
+    }
+
+    #[test]
+    fn test_heap_bump_allocation() {
+        let mut memory = BpfMemory::new(64, STACK_FRAME_SIZE);
+
+        let first = memory.alloc(5, 8).unwrap();
+        let second = memory.alloc(16, 8).unwrap();
+        let third = memory.alloc(8, 8).unwrap();
+        assert_eq!(first, MM_HEAP_START);
+        assert_eq!(second, MM_HEAP_START + 8);
+        assert_eq!(third, MM_HEAP_START + 24);
+
+        assert_eq!(memory.alloc(33, 8), None);
+        assert_eq!(memory.alloc(32, 8), Some(MM_HEAP_START + 32));
+        assert_eq!(memory.alloc(1, 1), None);
+
+        memory.reset();
+        // Alignments that are zero or not a power of two are refused outright
+        assert_eq!(memory.alloc(8, 0), None);
+        assert_eq!(memory.alloc(8, 12), None);
+        assert_eq!(memory.alloc(64, 8), Some(MM_HEAP_START));
+    }
+
+    #[test]
+    fn test_memory_config_validation() {
+        assert!(MemoryConfig::default().validate().is_ok());
+        assert!(MemoryConfig { heap_size: MAX_HEAP_SIZE, stack_size: 4096 }.validate().is_ok());
+        assert!(MemoryConfig { heap_size: MAX_HEAP_SIZE + 1, stack_size: 4096 }.validate().is_err());
+        assert!(MemoryConfig { heap_size: 1024, stack_size: 6000 }.validate().is_err());
+        assert!(MemoryConfig { heap_size: 1024, stack_size: 0 }.validate().is_err());
+    }
+
+    fn fault_kind(result: Result<impl Sized, TranspilerError>) -> MemoryErrorKind {
+        match result {
+            Err(TranspilerError::MemoryError(error)) => error.kind,
+            _ => panic!("expected a memory error"),
+        }
+    }
+
+    #[test]
+    fn test_memory_error_kinds() {
+        let mut memory = BpfMemory::new(64, 4096);
+        memory.map_account(MM_INPUT_START, vec![0; 4], false);
+        assert_eq!(memory.stack_top(), MM_STACK_START + 4096);
+
+        assert_eq!(fault_kind(memory.read_memory(MM_HEAP_START + 56, 16)), MemoryErrorKind::OutOfBounds);
+        assert_eq!(fault_kind(memory.read_memory(0x10, 1)), MemoryErrorKind::Unmapped);
+        assert_eq!(fault_kind(memory.write_memory(MM_INPUT_START, &[1])), MemoryErrorKind::WriteToReadOnly);
+        assert_eq!(fault_kind(memory.write_memory(MM_INPUT_START, &[1; 8])), MemoryErrorKind::OutOfBounds);
+
+        assert_eq!(fault_kind(memory.read_memory(0xFFFF_FFFF_FFFF_FFF8, 16)), MemoryErrorKind::OutOfBounds);
+        assert_eq!(fault_kind(memory.write_memory(u64::MAX, &[1, 2, 3])), MemoryErrorKind::OutOfBounds);
+
+        let error = memory.read_memory(0x10, 1).unwrap_err();
+        assert_eq!(error.to_string(), "Memory error: Unmapped access at 0x10 (size: 1)");
+    }
+
+    #[test]
+    fn test_region_at_top_of_address_space() {
+        let mut memory = BpfMemory::new(64, 4096);
+        memory.map_account(u64::MAX - 3, vec![7; 8], false);
+        let region = memory.regions().last().unwrap();
+        assert_eq!(region.end(), u64::MAX);
+
+        assert_eq!(memory.read_bytes(u64::MAX - 3, 3).unwrap(), &[7; 3]);
+        assert_eq!(fault_kind(memory.read_bytes(u64::MAX - 3, 8)), MemoryErrorKind::OutOfBounds);
+        assert_eq!(memory.read_bytes(u64::MAX - 1, 1).unwrap(), &[7]);
+    }
+
+    #[test]
+    fn test_misaligned_access() {
+        let mut memory = BpfMemory::new(64, 4096);
+        memory.write_memory(MM_HEAP_START + 8, &0x1122_3344_5566_7788u64.to_le_bytes()).unwrap();
+
+        assert_eq!(fault_kind(memory.read_memory(MM_HEAP_START + 1, 8)), MemoryErrorKind::Misaligned);
+        assert_eq!(fault_kind(memory.write_memory(MM_HEAP_START + 2, &[0; 4])), MemoryErrorKind::Misaligned);
+        assert!(memory.read_memory(MM_HEAP_START + 1, 3).is_ok());
+        assert!(memory.read_bytes(MM_HEAP_START + 1, 8).is_ok());
+
+        memory.set_allow_unaligned(true);
+        let value = memory.read_memory(MM_HEAP_START + 9, 8).unwrap();
+        assert_eq!(u64::from_le_bytes(value.try_into().unwrap()), 0x0011_2233_4455_6677);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::error::{TranspilerError, ZiskExecutionError};
+use sha2::{Digest, Sha256};
+
+/// Domain separation prefix for leaf hashes
+const LEAF_PREFIX: u8 = 0x00;
+/// Domain separation prefix for internal node hashes
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash function behind account leaves and Merkle nodes
+pub trait CommitmentHasher {
+    /// Digest of the concatenation of `parts`
+    fn hash(parts: &[&[u8]]) -> [u8; 32];
+}
+
+/// SHA-256, the default commitment hash
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256Hasher;
+
+impl CommitmentHasher for Sha256Hasher {
+    fn hash(parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// BLAKE3, for provers that expect it
+#[cfg(feature = "blake3")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3Hasher;
+
+#[cfg(feature = "blake3")]
+impl CommitmentHasher for Blake3Hasher {
+    fn hash(parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Account state committed as a Merkle leaf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountLeaf {
+    pub pubkey: [u8; 32],
+    pub lamports: u64,
+    pub data_hash: [u8; 32],
+}
+
+impl AccountLeaf {
+    /// Create a leaf from raw account data
+    pub fn new(pubkey: [u8; 32], lamports: u64, data: &[u8]) -> Self {
+        Self::new_with::<Sha256Hasher>(pubkey, lamports, data)
+    }
+
+    /// Create a leaf, hashing the data with `H`
+    pub fn new_with<H: CommitmentHasher>(pubkey: [u8; 32], lamports: u64, data: &[u8]) -> Self {
+        Self {
+            pubkey,
+            lamports,
+            data_hash: H::hash(&[data]),
+        }
+    }
+
+    /// Hash of the leaf as stored in the tree
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash_with::<Sha256Hasher>()
+    }
+
+    /// Hash of the leaf under `H`
+    pub fn hash_with<H: CommitmentHasher>(&self) -> [u8; 32] {
+        H::hash(&[&[LEAF_PREFIX], &self.pubkey, &self.lamports.to_le_bytes(), &self.data_hash])
+    }
+}
+
+/// Inclusion proof for a single leaf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerklePath {
+    /// Position of the leaf in the sorted leaf list
+    pub index: usize,
+    /// Number of leaves in the tree, which fixes its shape
+    pub leaf_count: usize,
+    /// Sibling hashes from the leaf level up to the root, skipping levels
+    /// where the node has none
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Binary Merkle tree over account leaves sorted by pubkey
+///
+/// On levels with an odd number of nodes the last node moves up unhashed,
+/// so no leaf set shares a root with the same set plus a repeated leaf.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    leaves: Vec<AccountLeaf>,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Build a tree, sorting leaves by pubkey so the root is order-independent
+    ///
+    /// Fails if two leaves share a pubkey.
+    pub fn new(leaves: Vec<AccountLeaf>) -> Result<Self, TranspilerError> {
+        Self::new_with::<Sha256Hasher>(leaves)
+    }
+
+    /// Build a tree whose leaves and nodes are hashed with `H`
+    pub fn new_with<H: CommitmentHasher>(mut leaves: Vec<AccountLeaf>) -> Result<Self, TranspilerError> {
+        leaves.sort_by_key(|leaf| leaf.pubkey);
+        if let Some(pair) = leaves.windows(2).find(|pair| pair[0].pubkey == pair[1].pubkey) {
+            let pubkey: String = pair[0].pubkey.iter().map(|byte| format!("{:02x}", byte)).collect();
+            return Err(TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidAccount {
+                message: format!("duplicate Merkle leaf for account {}", pubkey),
+            }));
+        }
+
+        let mut levels = Vec::new();
+        let mut current: Vec<[u8; 32]> = leaves.iter().map(AccountLeaf::hash_with::<H>).collect();
+
+        while current.len() > 1 {
+            let next = current
+                .chunks(2)
+                .map(|pair| pair.get(1).map_or(pair[0], |right| hash_node::<H>(&pair[0], right)))
+                .collect();
+            levels.push(current);
+            current = next;
+        }
+        levels.push(current);
+
+        Ok(Self { leaves, levels })
+    }
+
+    /// Merkle root (all zeros for an empty tree)
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or([0; 32])
+    }
+
+    /// Leaves in sorted order
+    pub fn leaves(&self) -> &[AccountLeaf] {
+        &self.leaves
+    }
+
+    /// Build an inclusion proof for the leaf at `index`
+    pub fn prove(&self, index: usize) -> Option<MerklePath> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            position /= 2;
+        }
+
+        Some(MerklePath { index, leaf_count: self.leaves.len(), siblings })
+    }
+}
+
+/// Verify that `leaf` is included under `root`
+pub fn verify(root: &[u8; 32], leaf: &AccountLeaf, path: &MerklePath) -> bool {
+    verify_with::<Sha256Hasher>(root, leaf, path)
+}
+
+/// Verify that `leaf` is included under a `root` built with `H`
+pub fn verify_with<H: CommitmentHasher>(root: &[u8; 32], leaf: &AccountLeaf, path: &MerklePath) -> bool {
+    if path.index >= path.leaf_count {
+        return false;
+    }
+
+    let mut hash = leaf.hash_with::<H>();
+    let mut position = path.index;
+    let mut width = path.leaf_count;
+    let mut siblings = path.siblings.iter();
+
+    while width > 1 {
+        // The last node of an odd level has no sibling and moves up as is
+        if position ^ 1 < width {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = if position.is_multiple_of(2) {
+                hash_node::<H>(&hash, sibling)
+            } else {
+                hash_node::<H>(sibling, &hash)
+            };
+        }
+        position /= 2;
+        width = width.div_ceil(2);
+    }
+
+    siblings.next().is_none() && hash == *root
+}
+
+fn hash_node<H: CommitmentHasher>(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    H::hash(&[&[NODE_PREFIX], left, right])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(id: u8, lamports: u64) -> AccountLeaf {
+        AccountLeaf::new([id; 32], lamports, &[id, id + 1])
+    }
+
+    #[test]
+    fn test_single_account_tree() {
+        let tree = MerkleTree::new(vec![leaf(1, 100)]).unwrap();
+        assert_eq!(tree.root(), leaf(1, 100).hash());
+
+        let path = tree.prove(0).unwrap();
+        assert!(path.siblings.is_empty());
+        assert!(verify(&tree.root(), &leaf(1, 100), &path));
+    }
+
+    #[test]
+    fn test_two_account_tree() {
+        let tree = MerkleTree::new(vec![leaf(2, 200), leaf(1, 100)]).unwrap();
+        assert_eq!(tree.root(), hash_node::<Sha256Hasher>(&leaf(1, 100).hash(), &leaf(2, 200).hash()));
+
+        let path = tree.prove(1).unwrap();
+        assert!(verify(&tree.root(), &leaf(2, 200), &path));
+        assert!(!verify(&tree.root(), &leaf(2, 201), &path));
+    }
+
+    #[test]
+    fn test_three_account_tree() {
+        let tree = MerkleTree::new(vec![leaf(3, 300), leaf(1, 100), leaf(2, 200)]).unwrap();
+        let reordered = MerkleTree::new(vec![leaf(2, 200), leaf(3, 300), leaf(1, 100)]).unwrap();
+        assert_eq!(tree.root(), reordered.root());
+
+        for (index, expected) in [leaf(1, 100), leaf(2, 200), leaf(3, 300)].iter().enumerate() {
+            let path = tree.prove(index).unwrap();
+            assert_eq!(path.siblings.len(), if index == 2 { 1 } else { 2 });
+            assert!(verify(&tree.root(), expected, &path));
+        }
+
+        assert!(tree.prove(3).is_none());
+    }
+
+    #[test]
+    fn test_odd_level_is_not_padded_with_a_copy() {
+        let (a, b, c) = (leaf(1, 100), leaf(2, 200), leaf(3, 300));
+        let tree = MerkleTree::new(vec![a.clone(), b.clone(), c.clone()]).unwrap();
+        let ab = hash_node::<Sha256Hasher>(&a.hash(), &b.hash());
+        assert_eq!(tree.root(), hash_node::<Sha256Hasher>(&ab, &c.hash()));
+
+        // The root `[a, b, c, c]` would have if duplicates were allowed
+        let padded = hash_node::<Sha256Hasher>(&ab, &hash_node::<Sha256Hasher>(&c.hash(), &c.hash()));
+        assert_ne!(tree.root(), padded);
+        assert!(MerkleTree::new(vec![a, b, c.clone(), c.clone()]).is_err());
+
+        // No phantom fourth leaf proves against the three-leaf root
+        let phantom = |leaf_count, siblings| MerklePath { index: 3, leaf_count, siblings };
+        assert!(!verify(&tree.root(), &c, &phantom(3, vec![ab])));
+        assert!(!verify(&tree.root(), &c, &phantom(4, vec![c.hash(), ab])));
+        assert!(!verify(&tree.root(), &c, &phantom(4, vec![ab])));
+    }
+
+    /// Sums bytes into the first digest byte; weak, but enough to tell trees apart
+    struct SumHasher;
+
+    impl CommitmentHasher for SumHasher {
+        fn hash(parts: &[&[u8]]) -> [u8; 32] {
+            let sum = parts.iter().flat_map(|part| part.iter()).fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+            let mut digest = [0; 32];
+            digest[0] = sum;
+            digest
+        }
+    }
+
+    #[test]
+    fn test_proofs_verify_under_their_own_hasher() {
+        let leaves = || vec![leaf(1, 100), leaf(2, 200), leaf(3, 300)];
+        let tree = MerkleTree::new_with::<SumHasher>(leaves()).unwrap();
+        assert_eq!(tree.root(), MerkleTree::new_with::<SumHasher>(leaves()).unwrap().root());
+        assert_ne!(tree.root(), MerkleTree::new(leaves()).unwrap().root());
+
+        let path = tree.prove(1).unwrap();
+        assert!(verify_with::<SumHasher>(&tree.root(), &leaf(2, 200), &path));
+        assert!(!verify(&tree.root(), &leaf(2, 200), &path));
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_roots_differ_from_sha256() {
+        let leaves = || vec![leaf(1, 100), leaf(2, 200)];
+        let blake3 = MerkleTree::new_with::<Blake3Hasher>(leaves()).unwrap().root();
+        assert_eq!(blake3, MerkleTree::new_with::<Blake3Hasher>(leaves()).unwrap().root());
+        assert_ne!(blake3, MerkleTree::new(leaves()).unwrap().root());
+        let sha256 = MerkleTree::new(leaves()).unwrap().root();
+        assert_eq!(MerkleTree::new_with::<Sha256Hasher>(leaves()).unwrap().root(), sha256);
+    }
+}
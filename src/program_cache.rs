@@ -0,0 +1,106 @@
+use crate::bpf_parser::BpfParser;
+use crate::error::TranspilerError;
+use crate::types::BpfProgram;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Parsed programs keyed by the SHA-256 of their input bytes
+#[derive(Debug, Default)]
+pub struct ProgramCache {
+    programs: HashMap<[u8; 32], BpfProgram>,
+    misses: usize,
+}
+
+impl ProgramCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached program for `bytecode`, parsing it with `parse` on a miss
+    pub fn get_or_parse<F>(&mut self, bytecode: &[u8], parse: F) -> Result<&BpfProgram, TranspilerError>
+    where
+        F: FnOnce(&[u8]) -> Result<BpfProgram, TranspilerError>,
+    {
+        let key: [u8; 32] = Sha256::digest(bytecode).into();
+        if !self.programs.contains_key(&key) {
+            let program = parse(bytecode)?;
+            self.misses += 1;
+            self.programs.insert(key, program);
+        }
+        Ok(&self.programs[&key])
+    }
+
+    /// Return the cached program for raw bytecode, parsing it with `parser` on a miss
+    pub fn get_or_parse_with(&mut self, parser: &BpfParser, bytecode: &[u8]) -> Result<&BpfProgram, TranspilerError> {
+        self.get_or_parse(bytecode, |bytes| parser.parse(bytes))
+    }
+
+    /// Number of times a program had to be parsed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Number of cached programs
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Drop all cached programs
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BpfZiskExecutor;
+
+    const PROGRAM: [u8; 16] = [
+        0xb7, 0x00, 0x00, 0x00, 0x2a, 0x00, 0x00, 0x00, // MOV64_IMM R0, 42
+        0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // EXIT
+    ];
+
+    #[test]
+    fn test_second_lookup_is_served_from_cache() {
+        let parser = BpfParser::new();
+        let mut cache = ProgramCache::new();
+        let mut parses = 0;
+
+        for _ in 0..2 {
+            let program = cache
+                .get_or_parse(&PROGRAM, |bytes| {
+                    parses += 1;
+                    parser.parse(bytes)
+                })
+                .unwrap();
+            assert_eq!(program.instructions.len(), 2);
+        }
+        assert_eq!(parses, 1);
+        assert_eq!(cache.misses(), 1);
+
+        let mut other = PROGRAM;
+        other[4] = 7;
+        cache.get_or_parse_with(&parser, &other).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.misses(), 2);
+    }
+
+    #[test]
+    fn test_executor_parse_cached() {
+        let mut executor = BpfZiskExecutor::new();
+        let first = executor.parse_cached(&PROGRAM).unwrap().instructions.clone();
+        let second = executor.parse_cached(&PROGRAM).unwrap().instructions.clone();
+
+        assert_eq!(first, second);
+        assert_eq!(executor.program_cache().misses(), 1);
+        assert!(executor.parse_cached(&PROGRAM[..12]).is_err());
+        assert_eq!(executor.program_cache().len(), 1);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::bpf_interpreter::BpfInterpreter;
+use crate::bpf_parser::BpfParser;
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::types::{BpfProgram, InputFormat};
+use crate::{load_with_format, ExecutionResult};
+use std::collections::HashMap;
+
+/// Parsed programs keyed by their 32-byte program id
+#[derive(Debug, Default)]
+pub struct ProgramRegistry {
+    parser: BpfParser,
+    programs: HashMap<[u8; 32], RegisteredProgram>,
+}
+
+#[derive(Debug)]
+struct RegisteredProgram {
+    program: BpfProgram,
+    /// Relocated ELF image, mapped at `MM_PROGRAM_START` on execution
+    program_image: Option<Vec<u8>>,
+}
+
+impl ProgramRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `elf` and register it under `program_id`, replacing any previous program
+    ///
+    /// Raw bytecode is accepted as well; input is treated as an ELF file if
+    /// it starts with the ELF magic.
+    pub fn load(&mut self, program_id: [u8; 32], elf: Vec<u8>) -> Result<(), TranspilerError> {
+        let (program, program_image) = load_with_format(&self.parser, &elf, InputFormat::detect(&elf))?;
+        self.programs.insert(program_id, RegisteredProgram { program, program_image });
+        Ok(())
+    }
+
+    /// Look up a registered program
+    pub fn get(&self, program_id: &[u8; 32]) -> Option<&BpfProgram> {
+        self.programs.get(program_id).map(|registered| &registered.program)
+    }
+
+    /// Number of registered programs
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Whether no programs are registered
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Execute the program registered under `program_id` on `interpreter`
+    ///
+    /// The instruction's accounts and data are not parameters: the caller
+    /// maps them into `interpreter`'s memory first, as [`crate::cpi::CpiSyscalls`]
+    /// does for a callee, because the interpreter owns the mapped regions
+    /// and the privileges they carry. An ELF program's image is mapped here.
+    pub fn execute_instruction(
+        &self,
+        program_id: &[u8; 32],
+        interpreter: &mut BpfInterpreter,
+    ) -> Result<ExecutionResult, TranspilerError> {
+        let registered = self.programs.get(program_id).ok_or_else(|| {
+            TranspilerError::ZiskExecutionError(ZiskExecutionError::UnknownProgram {
+                program_id: program_id.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            })
+        })?;
+        if let Some(image) = &registered.program_image {
+            interpreter.memory_mut().map_program(image.clone());
+        }
+        interpreter.set_program_id(*program_id);
+        interpreter.execute(&registered.program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn return_program(value: u8) -> Vec<u8> {
+        vec![
+            0xb7, 0x00, 0x00, 0x00, value, 0x00, 0x00, 0x00, // MOV64_IMM R0, value
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // EXIT
+        ]
+    }
+
+    #[test]
+    fn test_dispatch_by_program_id() {
+        let mut registry = ProgramRegistry::new();
+        registry.load([1; 32], return_program(1)).unwrap();
+        registry.load([2; 32], return_program(2)).unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let mut interpreter = BpfInterpreter::new();
+        assert_eq!(registry.execute_instruction(&[1; 32], &mut interpreter).unwrap().exit_code, 1);
+        assert_eq!(registry.execute_instruction(&[2; 32], &mut interpreter).unwrap().exit_code, 2);
+    }
+
+    #[test]
+    fn test_elf_programs_run_from_their_entry_with_rodata_mapped() {
+        // .rodata at file offset 64, .text at 72; execution enters past the first two instructions
+        let text = [
+            &[0xb7, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00][..], // MOV64_IMM R0, 1
+            &[0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],     // EXIT
+            &[0x18, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00],     // LD_IMM64 R0, .rodata
+            &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            &[0x79, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],     // LDX64 R0, [R0+0]
+            &[0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],     // EXIT
+        ]
+        .concat();
+        // R_BPF_64_RELATIVE at the LD_IMM64
+        let relocation = [88u64.to_le_bytes(), 8u64.to_le_bytes()].concat();
+        let mut elf = crate::elf_loader::build_test_elf(&[
+            (".rodata", 1, &7u64.to_le_bytes()),
+            (".text", 1, &text),
+            (".rel.dyn", 9, &relocation),
+        ]);
+        elf[0x18..0x20].copy_from_slice(&88u64.to_le_bytes());
+
+        let mut registry = ProgramRegistry::new();
+        registry.load([1; 32], elf).unwrap();
+        registry.load([2; 32], return_program(2)).unwrap();
+        assert_eq!(registry.get(&[1; 32]).unwrap().entry_pc, 2);
+
+        let mut interpreter = BpfInterpreter::new();
+        assert_eq!(registry.execute_instruction(&[1; 32], &mut interpreter).unwrap().exit_code, 7);
+        assert_eq!(registry.execute_instruction(&[2; 32], &mut BpfInterpreter::new()).unwrap().exit_code, 2);
+    }
+
+    #[test]
+    fn test_unregistered_program_is_rejected() {
+        let registry = ProgramRegistry::new();
+        let error = registry
+            .execute_instruction(&[0xab; 32], &mut BpfInterpreter::new())
+            .unwrap_err();
+        assert!(error.to_string().contains("Unknown program: abab"));
+        assert!(registry.get(&[0xab; 32]).is_none());
+    }
+}
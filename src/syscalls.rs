@@ -0,0 +1,503 @@
+use crate::error::{InterpreterError, MemoryError, MemoryErrorKind, TranspilerError};
+use crate::memory::BpfMemory;
+use crate::sysvars::{Clock, Rent, Sysvars};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// `sol_log_` syscall hash
+pub const SOL_LOG: u64 = syscall_hash("sol_log_");
+/// `sol_log_64_` syscall hash
+pub const SOL_LOG_64: u64 = syscall_hash("sol_log_64_");
+/// `sol_log_data` syscall hash
+pub const SOL_LOG_DATA: u64 = syscall_hash("sol_log_data");
+/// `sol_sha256` syscall hash
+pub const SOL_SHA256: u64 = syscall_hash("sol_sha256");
+/// `sol_get_clock_sysvar` syscall hash
+pub const SOL_GET_CLOCK_SYSVAR: u64 = syscall_hash("sol_get_clock_sysvar");
+/// `sol_get_rent_sysvar` syscall hash
+pub const SOL_GET_RENT_SYSVAR: u64 = syscall_hash("sol_get_rent_sysvar");
+/// `sol_set_return_data` syscall hash
+pub const SOL_SET_RETURN_DATA: u64 = syscall_hash("sol_set_return_data");
+/// `sol_get_return_data` syscall hash
+pub const SOL_GET_RETURN_DATA: u64 = syscall_hash("sol_get_return_data");
+/// `sol_alloc_free_` syscall hash
+pub const SOL_ALLOC_FREE: u64 = syscall_hash("sol_alloc_free_");
+/// `sol_invoke_signed_c` syscall hash
+pub const SOL_INVOKE_SIGNED_C: u64 = syscall_hash("sol_invoke_signed_c");
+
+/// Syscalls known to the runtime, by symbol name
+pub const SYSCALL_NAMES: &[&str] = &[
+    "abort",
+    "sol_panic_",
+    "sol_log_",
+    "sol_log_64_",
+    "sol_log_compute_units_",
+    "sol_log_pubkey",
+    "sol_log_data",
+    "sol_sha256",
+    "sol_memcpy_",
+    "sol_memmove_",
+    "sol_memset_",
+    "sol_memcmp_",
+    "sol_set_return_data",
+    "sol_get_return_data",
+    "sol_alloc_free_",
+    "sol_get_clock_sysvar",
+    "sol_get_rent_sysvar",
+    "sol_invoke_signed_c",
+    "sol_invoke_signed_rust",
+];
+
+/// Compute units charged for a syscall before any per-byte cost (Solana's `syscall_base_cost`)
+pub const SYSCALL_BASE_COST: u64 = 100;
+
+/// Compute units charged per syscall, on top of the `CALL` instruction itself
+///
+/// Defaults follow Solana's published costs; their per-byte components
+/// are not modeled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostModel {
+    pub log: u64,
+    pub log_64: u64,
+    pub log_data: u64,
+    pub sha256: u64,
+    pub clock_sysvar: u64,
+    pub rent_sysvar: u64,
+    pub set_return_data: u64,
+    pub get_return_data: u64,
+    pub alloc_free: u64,
+    pub invoke: u64,
+    /// Any other syscall, including unknown ones ignored by policy
+    pub syscall_base: u64,
+}
+
+impl CostModel {
+    /// Compute units charged for syscall `id`
+    pub fn cost(&self, id: u64) -> u64 {
+        match id {
+            SOL_LOG => self.log,
+            SOL_LOG_64 => self.log_64,
+            SOL_LOG_DATA => self.log_data,
+            SOL_SHA256 => self.sha256,
+            SOL_GET_CLOCK_SYSVAR => self.clock_sysvar,
+            SOL_GET_RENT_SYSVAR => self.rent_sysvar,
+            SOL_SET_RETURN_DATA => self.set_return_data,
+            SOL_GET_RETURN_DATA => self.get_return_data,
+            SOL_ALLOC_FREE => self.alloc_free,
+            SOL_INVOKE_SIGNED_C => self.invoke,
+            _ => self.syscall_base,
+        }
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            log: SYSCALL_BASE_COST,
+            log_64: SYSCALL_BASE_COST,
+            log_data: SYSCALL_BASE_COST,
+            sha256: 85,
+            // sysvar_base_cost plus the sysvar's size
+            clock_sysvar: SYSCALL_BASE_COST + Clock::SIZE as u64,
+            rent_sysvar: SYSCALL_BASE_COST + Rent::SIZE as u64,
+            set_return_data: SYSCALL_BASE_COST,
+            get_return_data: SYSCALL_BASE_COST,
+            alloc_free: 0,
+            invoke: 1000,
+            syscall_base: SYSCALL_BASE_COST,
+        }
+    }
+}
+
+/// Solana's limit on `sol_set_return_data` payloads
+pub const MAX_RETURN_DATA: usize = 1024;
+
+/// Alignment of `sol_alloc_free_` allocations (`align_of::<u128>()` on SBF)
+const HEAP_ALIGN: u64 = 8;
+
+/// Size of a `SolBytes { addr: u64, len: u64 }` slice descriptor
+const SOL_BYTES_SIZE: u64 = 16;
+
+/// Hash of a syscall symbol name, as used in `CALL` immediates
+///
+/// This is rBPF's `hash_symbol_name`: murmur3_32 with seed 0.
+pub const fn syscall_hash(name: &str) -> u64 {
+    const C1: u32 = 0xcc9e2d51;
+    const C2: u32 = 0x1b873593;
+
+    let bytes = name.as_bytes();
+    let mut hash = 0u32;
+    let mut i = 0;
+    while i + 4 <= bytes.len() {
+        let mut k = u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash = (hash ^ k).rotate_left(13).wrapping_mul(5).wrapping_add(0xe6546b64);
+        i += 4;
+    }
+
+    let mut k = 0u32;
+    let mut shift = 0;
+    while i < bytes.len() {
+        k |= (bytes[i] as u32) << shift;
+        shift += 8;
+        i += 1;
+    }
+    if shift > 0 {
+        hash ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    hash ^= bytes.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+    hash as u64
+}
+
+/// Name of the known syscall with hash `id`
+pub fn syscall_name(id: u64) -> Option<&'static str> {
+    SYSCALL_NAMES.iter().copied().find(|name| syscall_hash(name) == id)
+}
+
+/// Render a `CALL` the way a disassembler would
+///
+/// Syscalls (`src_reg == 0`) show their symbol name when the hash is known.
+/// Other calls are relative to the next instruction; they show the target's
+/// byte offset in hex and the relative distance in instructions.
+pub fn describe_call(pc: usize, src_reg: u8, immediate: i64) -> String {
+    if src_reg == 0 {
+        let id = immediate as u32 as u64;
+        return match syscall_name(id) {
+            Some(name) => format!("call {}", name),
+            None => format!("call syscall_{:#010x}", id),
+        };
+    }
+    let target = (pc as i64 + 1 + immediate) * 8;
+    format!("call function_{:#x} ({:+})", target, immediate)
+}
+
+/// Severity of a [`LogEntry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Runtime diagnostics, such as CPI invocations
+    Debug,
+    /// Output a program asked to log
+    Info,
+}
+
+/// One line of program log output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    /// Program that produced the entry, if known
+    pub program_id: Option<[u8; 32]>,
+    /// Line as the Solana runtime prints it, e.g. `Program log: hello`
+    pub message: String,
+}
+
+/// Data set by `sol_set_return_data`, tagged with the program that set it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReturnData {
+    pub program_id: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+/// Interpreter state exposed to syscall handlers
+pub struct SyscallContext<'a> {
+    pub registers: &'a mut [u64; 11],
+    pub memory: &'a mut BpfMemory,
+    pub logs: &'a mut Vec<LogEntry>,
+    pub sysvars: &'a Sysvars,
+    /// Id of the executing program
+    pub program_id: &'a [u8; 32],
+    pub return_data: &'a mut ReturnData,
+    /// Largest payload accepted by `sol_set_return_data`
+    pub max_return_data: usize,
+}
+
+impl SyscallContext<'_> {
+    /// Append a log entry attributed to the executing program
+    pub fn log(&mut self, level: LogLevel, message: String) {
+        self.logs.push(LogEntry { level, program_id: Some(*self.program_id), message });
+    }
+
+    /// Read `len` bytes of program memory at `address`
+    pub fn read_memory(&self, address: u64, len: u64) -> Result<&[u8], TranspilerError> {
+        self.memory.read_bytes(address, len as usize)
+    }
+
+    /// Write `data` to program memory at `address`
+    pub fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), TranspilerError> {
+        self.memory.write_bytes(address, data)
+    }
+
+    /// Read `count` slices described by a `SolBytes` array at `address`
+    pub fn read_slices(&self, address: u64, count: u64) -> Result<Vec<&[u8]>, TranspilerError> {
+        let descriptors_len = count.checked_mul(SOL_BYTES_SIZE).ok_or(MemoryError {
+            addr: address,
+            size: usize::MAX,
+            kind: MemoryErrorKind::OutOfBounds,
+        })?;
+        let descriptors = self.read_memory(address, descriptors_len)?;
+
+        descriptors
+            .chunks_exact(SOL_BYTES_SIZE as usize)
+            .map(|descriptor| {
+                let slice_addr = u64::from_le_bytes(descriptor[..8].try_into().unwrap());
+                let slice_len = u64::from_le_bytes(descriptor[8..].try_into().unwrap());
+                self.read_memory(slice_addr, slice_len)
+            })
+            .collect()
+    }
+}
+
+/// Handler for BPF `CALL` instructions targeting syscalls
+pub trait SyscallHandler {
+    /// Handle syscall `id`, returning `Ok(false)` if the id is not handled
+    fn dispatch(&mut self, ctx: &mut SyscallContext, id: u64) -> Result<bool, TranspilerError>;
+}
+
+/// Built-in syscalls used when no custom handler claims the call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSyscalls;
+
+impl SyscallHandler for DefaultSyscalls {
+    fn dispatch(&mut self, ctx: &mut SyscallContext, id: u64) -> Result<bool, TranspilerError> {
+        match id {
+            SOL_LOG => {
+                let message = ctx.read_memory(ctx.registers[1], ctx.registers[2])?;
+                let message = String::from_utf8_lossy(message).into_owned();
+                ctx.log(LogLevel::Info, format!("Program log: {}", message));
+            }
+            SOL_LOG_64 => {
+                let values = &ctx.registers[1..6];
+                let message = format!(
+                    "Program log: {:#x}, {:#x}, {:#x}, {:#x}, {:#x}",
+                    values[0], values[1], values[2], values[3], values[4]
+                );
+                ctx.log(LogLevel::Info, message);
+            }
+            SOL_LOG_DATA => {
+                let fields: Vec<String> = ctx
+                    .read_slices(ctx.registers[1], ctx.registers[2])?
+                    .into_iter()
+                    .map(|field| BASE64.encode(field))
+                    .collect();
+                ctx.log(LogLevel::Info, format!("Program data: {}", fields.join(" ")));
+            }
+            SOL_SHA256 => {
+                // r1 = SolBytes array, r2 = slice count, r3 = 32-byte result buffer
+                let mut hasher = Sha256::new();
+                for slice in ctx.read_slices(ctx.registers[1], ctx.registers[2])? {
+                    hasher.update(slice);
+                }
+                let digest = hasher.finalize();
+                ctx.write_memory(ctx.registers[3], &digest)?;
+            }
+            SOL_GET_CLOCK_SYSVAR => {
+                let clock = ctx.sysvars.clock.to_bytes();
+                ctx.write_memory(ctx.registers[1], &clock)?;
+            }
+            SOL_GET_RENT_SYSVAR => {
+                let rent = ctx.sysvars.rent.to_bytes();
+                ctx.write_memory(ctx.registers[1], &rent)?;
+            }
+            SOL_SET_RETURN_DATA => {
+                if ctx.registers[2] > ctx.max_return_data as u64 {
+                    return Err(TranspilerError::InterpreterError(InterpreterError::ReturnDataTooLarge {
+                        len: ctx.registers[2],
+                        max: ctx.max_return_data,
+                    }));
+                }
+                let data = ctx.read_memory(ctx.registers[1], ctx.registers[2])?.to_vec();
+                *ctx.return_data = ReturnData { program_id: *ctx.program_id, data };
+            }
+            SOL_ALLOC_FREE => {
+                // r1 = size, r2 = pointer to free; freeing is a no-op for a bump allocator.
+                // r0 is the allocation address, or 0 when the heap is exhausted.
+                ctx.registers[0] = match ctx.registers[2] {
+                    0 => ctx.memory.alloc(ctx.registers[1], HEAP_ALIGN).unwrap_or(0),
+                    _ => 0,
+                };
+                return Ok(true);
+            }
+            SOL_GET_RETURN_DATA => {
+                // r0 is the full length, even if only a prefix fits the caller's buffer
+                let ReturnData { program_id, data } = ctx.return_data.clone();
+                if !data.is_empty() {
+                    let copy_len = data.len().min(ctx.registers[2] as usize);
+                    ctx.write_memory(ctx.registers[1], &data[..copy_len])?;
+                    ctx.write_memory(ctx.registers[3], &program_id)?;
+                }
+                ctx.registers[0] = data.len() as u64;
+                return Ok(true);
+            }
+            _ => return Ok(false),
+        }
+
+        ctx.registers[0] = 0;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MM_HEAP_START;
+    use std::sync::OnceLock;
+
+    fn context<'a>(
+        registers: &'a mut [u64; 11],
+        memory: &'a mut BpfMemory,
+        logs: &'a mut Vec<LogEntry>,
+        return_data: &'a mut ReturnData,
+    ) -> SyscallContext<'a> {
+        static SYSVARS: OnceLock<Sysvars> = OnceLock::new();
+        SyscallContext {
+            registers,
+            memory,
+            logs,
+            sysvars: SYSVARS.get_or_init(Sysvars::default),
+            program_id: &[0; 32],
+            return_data,
+            max_return_data: MAX_RETURN_DATA,
+        }
+    }
+
+    /// Write each slice at its address and their `SolBytes` descriptors at `MM_HEAP_START`
+    fn write_sol_bytes(memory: &mut BpfMemory, slices: &[(u64, &[u8])]) {
+        let mut descriptors = Vec::new();
+        for (addr, bytes) in slices {
+            memory.write_memory(*addr, bytes).unwrap();
+            descriptors.extend_from_slice(&addr.to_le_bytes());
+            descriptors.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        }
+        memory.write_memory(MM_HEAP_START, &descriptors).unwrap();
+    }
+
+    #[test]
+    fn test_syscall_hashes_match_runtime() {
+        assert_eq!(syscall_hash("sol_log_"), 0x207559bd);
+        assert_eq!(syscall_hash("abort"), 0xb6fc1a11);
+        assert_eq!(syscall_hash("sol_sha256"), 0x11f49d86);
+        assert_eq!(syscall_hash("sol_invoke_signed_rust"), 0xd7449092);
+        assert_eq!(syscall_name(0x5c2a3178), Some("sol_log_64_"));
+        assert_eq!(syscall_name(0xdead), None);
+    }
+
+    #[test]
+    fn test_describe_call_resolves_targets() {
+        assert_eq!(describe_call(3, 0, SOL_LOG as u32 as i32 as i64), "call sol_log_");
+        assert_eq!(describe_call(3, 0, 0xdead), "call syscall_0x0000dead");
+        assert_eq!(describe_call(3, 1, 12), "call function_0x80 (+12)");
+        assert_eq!(describe_call(8, 1, -5), "call function_0x20 (-5)");
+    }
+
+    #[test]
+    fn test_sol_log_data_encodes_each_slice() {
+        let (mut registers, mut memory, mut logs) = ([0u64; 11], BpfMemory::default(), Vec::new());
+        write_sol_bytes(
+            &mut memory,
+            &[(MM_HEAP_START + 0x100, b"hello"), (MM_HEAP_START + 0x200, &[0xde, 0xad, 0xbe, 0xef])],
+        );
+
+        registers[1] = MM_HEAP_START;
+        registers[2] = 2;
+        let mut return_data = ReturnData::default();
+        let mut ctx = context(&mut registers, &mut memory, &mut logs, &mut return_data);
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_LOG_DATA).unwrap());
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].message, "Program data: aGVsbG8= 3q2+7w==");
+    }
+
+    #[test]
+    fn test_sol_sha256_hashes_concatenated_slices() {
+        let (mut registers, mut memory, mut logs) = ([0u64; 11], BpfMemory::default(), Vec::new());
+        write_sol_bytes(&mut memory, &[(MM_HEAP_START + 0x100, b"hello "), (MM_HEAP_START + 0x200, b"world")]);
+
+        registers[1] = MM_HEAP_START;
+        registers[2] = 2;
+        registers[3] = MM_HEAP_START + 0x300;
+        let mut return_data = ReturnData::default();
+        let mut ctx = context(&mut registers, &mut memory, &mut logs, &mut return_data);
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_SHA256).unwrap());
+        let digest = ctx.read_memory(MM_HEAP_START + 0x300, 32).unwrap();
+        assert_eq!(digest, Sha256::digest(b"hello world").as_slice());
+    }
+
+    #[test]
+    fn test_sol_log_data_rejects_out_of_bounds_slices() {
+        let (mut registers, mut memory, mut logs) = ([0u64; 11], BpfMemory::default(), Vec::new());
+        // One slice whose length runs far past the heap
+        write_sol_bytes(&mut memory, &[(MM_HEAP_START + 0x10, &[])]);
+        memory.write_memory(MM_HEAP_START + 8, &0x10_0000u64.to_le_bytes()).unwrap();
+
+        registers[1] = MM_HEAP_START;
+        registers[2] = 1;
+        let mut return_data = ReturnData::default();
+        let mut ctx = context(&mut registers, &mut memory, &mut logs, &mut return_data);
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_LOG_DATA).is_err());
+
+        ctx.registers[2] = u64::MAX;
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_LOG_DATA).is_err());
+
+        ctx.registers[2] = 1;
+        ctx.write_memory(MM_HEAP_START + 8, &u64::MAX.to_le_bytes()).unwrap();
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_LOG_DATA).is_err());
+        assert!(logs.is_empty());
+    }
+
+    #[test]
+    fn test_return_data_is_capped() {
+        let (mut registers, mut memory, mut logs) = ([0u64; 11], BpfMemory::default(), Vec::new());
+        let mut return_data = ReturnData::default();
+        let payload: Vec<u8> = (0..=MAX_RETURN_DATA).map(|i| i as u8).collect();
+        memory.write_memory(MM_HEAP_START, &payload).unwrap();
+
+        let mut ctx = context(&mut registers, &mut memory, &mut logs, &mut return_data);
+        ctx.program_id = &[7; 32];
+        ctx.registers[1] = MM_HEAP_START;
+        ctx.registers[2] = MAX_RETURN_DATA as u64 + 1;
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_SET_RETURN_DATA).is_err());
+        assert!(ctx.return_data.data.is_empty());
+
+        ctx.registers[2] = MAX_RETURN_DATA as u64;
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_SET_RETURN_DATA).unwrap());
+        assert_eq!(ctx.return_data.data.len(), MAX_RETURN_DATA);
+
+        ctx.registers[1] = MM_HEAP_START + 0x1000;
+        ctx.registers[2] = 4;
+        ctx.registers[3] = MM_HEAP_START + 0x2000;
+        assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_GET_RETURN_DATA).unwrap());
+        assert_eq!(ctx.registers[0], MAX_RETURN_DATA as u64);
+        assert_eq!(ctx.read_memory(MM_HEAP_START + 0x1000, 5).unwrap(), &[0, 1, 2, 3, 0]);
+        assert_eq!(ctx.read_memory(MM_HEAP_START + 0x2000, 32).unwrap(), &[7; 32]);
+    }
+
+    #[test]
+    fn test_sol_alloc_free_returns_distinct_blocks_until_exhausted() {
+        let (mut registers, mut logs) = ([0u64; 11], Vec::new());
+        let mut memory = BpfMemory::new(32, crate::memory::STACK_FRAME_SIZE);
+        let mut return_data = ReturnData::default();
+        let mut ctx = context(&mut registers, &mut memory, &mut logs, &mut return_data);
+
+        let mut blocks = Vec::new();
+        for size in [3, 12, 8] {
+            ctx.registers[1] = size;
+            assert!(DefaultSyscalls.dispatch(&mut ctx, SOL_ALLOC_FREE).unwrap());
+            blocks.push((ctx.registers[0], size));
+        }
+        assert_eq!(blocks, vec![(MM_HEAP_START, 3), (MM_HEAP_START + 8, 12), (MM_HEAP_START + 24, 8)]);
+
+        ctx.registers[1] = 1;
+        DefaultSyscalls.dispatch(&mut ctx, SOL_ALLOC_FREE).unwrap();
+        assert_eq!(ctx.registers[0], 0);
+
+        ctx.registers[2] = MM_HEAP_START;
+        ctx.registers[0] = 0xdead;
+        DefaultSyscalls.dispatch(&mut ctx, SOL_ALLOC_FREE).unwrap();
+        assert_eq!(ctx.registers[0], 0);
+    }
+}
@@ -0,0 +1,68 @@
+/// Clock sysvar, laid out as Solana's `#[repr(C)] Clock`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Clock {
+    pub slot: u64,
+    pub epoch_start_timestamp: i64,
+    pub epoch: u64,
+    pub leader_schedule_epoch: u64,
+    pub unix_timestamp: i64,
+}
+
+impl Clock {
+    /// Serialized size in bytes
+    pub const SIZE: usize = 40;
+
+    /// Serialize in the layout programs read from `sol_get_clock_sysvar`
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.slot.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.epoch_start_timestamp.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.epoch.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.leader_schedule_epoch.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.unix_timestamp.to_le_bytes());
+        bytes
+    }
+}
+
+/// Rent sysvar, laid out as Solana's `#[repr(C)] Rent`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rent {
+    pub lamports_per_byte_year: u64,
+    pub exemption_threshold: f64,
+    pub burn_percent: u8,
+}
+
+impl Rent {
+    /// Serialized size in bytes, including trailing padding
+    pub const SIZE: usize = 24;
+
+    /// Serialize in the layout programs read from `sol_get_rent_sysvar`
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.lamports_per_byte_year.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.exemption_threshold.to_le_bytes());
+        bytes[16] = self.burn_percent;
+        bytes
+    }
+}
+
+impl Default for Rent {
+    /// Solana's genesis rent parameters
+    fn default() -> Self {
+        Self {
+            lamports_per_byte_year: 3480,
+            exemption_threshold: 2.0,
+            burn_percent: 50,
+        }
+    }
+}
+
+/// Sysvars visible to a program
+///
+/// The defaults are fixed rather than taken from the host so that
+/// executions, and therefore proofs, are reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sysvars {
+    pub clock: Clock,
+    pub rent: Rent,
+}
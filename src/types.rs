@@ -1,92 +1,27 @@
+use crate::error::{BpfParseError, TranspilerError};
 use std::collections::HashMap;
 
-/// BPF instruction structure
-#[derive(Debug, Clone, PartialEq)]
-pub struct BpfInstruction {
-    pub opcode: BpfOpcode,
-    pub dst_reg: u8,
-    pub src_reg: u8,
-    pub immediate: i64,
-    pub offset: i16,
+pub use crate::core_bpf::{BpfInstruction, BpfOpcode};
+
+/// Encoding of a program passed to the executor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    /// Bare 8-byte BPF instructions
+    #[default]
+    RawBytecode,
+    /// ELF file whose `.text` section holds the instructions
+    Elf,
 }
 
-/// BPF opcodes supported by our transpiler
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum BpfOpcode {
-    // ALU operations
-    Add64Imm = 0x07,      // ADD64_IMM
-    Add64Reg = 0x0f,      // ADD64_REG
-    Sub64Imm = 0x17,      // SUB64_IMM
-    Sub64Reg = 0x1f,      // SUB64_REG
-    Mul64Imm = 0x27,      // MUL64_IMM
-    Mul64Reg = 0x2f,      // MUL64_REG
-    Div64Imm = 0x37,      // DIV64_IMM
-    Div64Reg = 0x3f,      // DIV64_REG
-    Or64Imm = 0x47,       // OR64_IMM
-    Or64Reg = 0x4f,       // OR64_REG
-    And64Imm = 0x57,      // AND64_IMM
-    And64Reg = 0x5f,      // AND64_REG
-    Lsh64Imm = 0x67,      // LSH64_IMM
-    Lsh64Reg = 0x6f,      // LSH64_REG
-    Rsh64Imm = 0x77,      // RSH64_IMM
-    Rsh64Reg = 0x7f,      // RSH64_REG
-    Neg64 = 0x87,         // NEG64
-    Mod64Imm = 0x97,      // MOD64_IMM
-    Mod64Reg = 0x9f,      // MOD64_REG
-    Xor64Imm = 0xa7,      // XOR64_IMM
-    Xor64Reg = 0xaf,      // XOR64_REG
-    Mov64Imm = 0xb7,      // MOV64_IMM
-    Mov64Reg = 0xbf,      // MOV64_REG
-    
-    // Memory operations
-    LdImm64 = 0x18,       // LD_IMM64
-    LdAbs8 = 0x30,        // LD_ABS8
-    LdAbs16 = 0x28,       // LD_ABS16
-    LdAbs32 = 0x20,       // LD_ABS32
-    LdAbs64 = 0x19,       // LD_ABS64 (different from LD_IMM64)
-    LdInd8 = 0x38,        // LD_IND8
-    LdInd16 = 0x31,       // LD_IND16 (different from LD_ABS8)
-    LdInd32 = 0x29,       // LD_IND32 (different from LD_ABS16)
-    LdInd64 = 0x21,       // LD_IND64 (different from LD_ABS32)
-    Ldx8 = 0x71,          // LDX8
-    Ldx16 = 0x69,         // LDX16
-    Ldx32 = 0x61,         // LDX32
-    Ldx64 = 0x79,         // LDX64
-    St8 = 0x72,           // ST8
-    St16 = 0x6a,          // ST16
-    St32 = 0x62,          // ST32
-    St64 = 0x7a,          // ST64
-    Stx8 = 0x73,          // STX8
-    Stx16 = 0x6b,         // STX16
-    Stx32 = 0x63,         // STX32
-    Stx64 = 0x7b,         // STX64
-    
-    // Branch operations
-    Ja = 0x05,            // JA
-    JeqImm = 0x15,        // JEQ_IMM
-    JeqReg = 0x1d,        // JEQ_REG
-    JgtImm = 0x25,        // JGT_IMM
-    JgtReg = 0x2d,        // JGT_REG
-    JgeImm = 0x35,        // JGE_IMM
-    JgeReg = 0x3d,        // JGE_REG
-    JltImm = 0xa5,        // JLT_IMM
-    JltReg = 0xad,        // JLT_REG
-    JleImm = 0xb5,        // JLE_IMM
-    JleReg = 0xbd,        // JLE_REG
-    JsetImm = 0x45,       // JSET_IMM
-    JsetReg = 0x4d,       // JSET_REG
-    JneImm = 0x55,        // JNE_IMM
-    JneReg = 0x5d,        // JNE_REG
-    JsgtImm = 0x65,       // JSGT_IMM
-    JsgtReg = 0x6d,       // JSGT_REG
-    JsgeImm = 0x75,       // JSGE_IMM
-    JsgeReg = 0x7d,       // JSGE_REG
-    JsltImm = 0xc5,       // JSLT_IMM
-    JsltReg = 0xcd,       // JSLT_REG
-    JsleImm = 0xd5,       // JSLE_IMM
-    JsleReg = 0xdd,       // JSLE_REG
-    Call = 0x85,          // CALL
-    Exit = 0x95,          // EXIT
+impl InputFormat {
+    /// `Elf` if `bytes` start with the ELF magic, otherwise `RawBytecode`
+    pub fn detect(bytes: &[u8]) -> Self {
+        if crate::elf_loader::is_elf(bytes) {
+            Self::Elf
+        } else {
+            Self::RawBytecode
+        }
+    }
 }
 
 /// BPF program structure
@@ -94,7 +29,49 @@ pub enum BpfOpcode {
 pub struct BpfProgram {
     pub instructions: Vec<BpfInstruction>,
     pub labels: HashMap<String, usize>,
+    /// Bytecode length in bytes; `LD_IMM64` counts as 16
     pub size: usize,
+    /// Program counter at which execution starts
+    pub entry_pc: usize,
+}
+
+impl BpfProgram {
+    /// Pre-decoded instruction at program counter `pc`
+    ///
+    /// Instructions are decoded once by the parser, so interpreters index
+    /// this rather than re-decoding bytecode on every step.
+    pub fn instruction_at(&self, pc: usize) -> Option<&BpfInstruction> {
+        self.instructions.get(pc)
+    }
+
+    /// Number of instructions; `LD_IMM64` counts as one
+    pub fn instruction_count(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Byte offset in the bytecode of the instruction at `pc`
+    ///
+    /// `LD_IMM64` occupies two 8-byte slots but a single program counter.
+    pub fn byte_offset(&self, pc: usize) -> usize {
+        self.instructions
+            .iter()
+            .take(pc)
+            .map(|instruction| if instruction.opcode == BpfOpcode::LdImm64 { 16 } else { 8 })
+            .sum()
+    }
+
+    /// Start execution at the instruction `byte_offset` bytes into the bytecode
+    ///
+    /// The offset must be 8-aligned and land on an instruction, not inside
+    /// the second slot of an `LD_IMM64`.
+    pub fn set_entry_offset(&mut self, byte_offset: usize) -> Result<(), TranspilerError> {
+        // Instruction offsets are multiples of 8, so misaligned offsets never match
+        let entry_pc = (0..self.instructions.len())
+            .find(|pc| self.byte_offset(*pc) == byte_offset)
+            .ok_or(TranspilerError::BpfParseError(BpfParseError::InvalidEntrypoint { offset: byte_offset }))?;
+        self.entry_pc = entry_pc;
+        Ok(())
+    }
 }
 
 /// Result of BPF program execution
@@ -106,6 +83,50 @@ pub struct ExecutionResult {
     pub execution_time: std::time::Duration,
 }
 
+/// Support for one opcode used by a program, per execution path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeSupport {
+    pub opcode: BpfOpcode,
+    /// Occurrences in the program
+    pub count: usize,
+    /// Executable by the native interpreter
+    pub interpreter: bool,
+    /// Lowered by the ZisK guest generator
+    pub zisk: bool,
+}
+
+/// Which execution path can run a program, opcode by opcode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatReport {
+    /// Distinct opcodes in the program, in order of first use
+    pub opcodes: Vec<OpcodeSupport>,
+}
+
+impl CompatReport {
+    /// Opcodes the interpreter runs but the ZisK guest does not
+    pub fn interpreter_only(&self) -> Vec<BpfOpcode> {
+        self.opcodes
+            .iter()
+            .filter(|support| support.interpreter && !support.zisk)
+            .map(|support| support.opcode)
+            .collect()
+    }
+
+    /// Opcodes the ZisK guest runs but the interpreter does not
+    pub fn zisk_only(&self) -> Vec<BpfOpcode> {
+        self.opcodes
+            .iter()
+            .filter(|support| support.zisk && !support.interpreter)
+            .map(|support| support.opcode)
+            .collect()
+    }
+
+    /// Whether every opcode is supported on both paths
+    pub fn fully_compatible(&self) -> bool {
+        self.opcodes.iter().all(|support| support.interpreter && support.zisk)
+    }
+}
+
 /// Register mapping for BPF to RISC-V conversion
 #[derive(Debug, Clone)]
 pub struct RegisterMapping {
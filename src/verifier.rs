@@ -0,0 +1,163 @@
+use crate::error::VerifierError;
+use crate::types::{BpfInstruction, BpfOpcode, BpfProgram};
+
+/// Registers defined on entry: r1 (input pointer) and r10 (frame pointer)
+const ENTRY_DEFINED: u16 = 1 << 1 | 1 << 10;
+
+/// Check that every register is written before it is read on all paths
+///
+/// This is a forward dataflow pass from the program's entry point: a
+/// register counts as defined at an instruction only if it is defined along
+/// every path reaching it. Instructions unreachable from the entry are not
+/// checked. Errors are reported in instruction order.
+pub fn verify_register_init(program: &BpfProgram) -> Result<(), Vec<VerifierError>> {
+    let instructions = &program.instructions;
+    let mut defined_in: Vec<Option<u16>> = vec![None; instructions.len()];
+    let mut worklist = Vec::new();
+    if program.entry_pc < instructions.len() {
+        defined_in[program.entry_pc] = Some(ENTRY_DEFINED);
+        worklist.push(program.entry_pc);
+    }
+
+    while let Some(pc) = worklist.pop() {
+        let instruction = &instructions[pc];
+        let (_, writes) = register_effects(instruction);
+        let defined_out = defined_in[pc].unwrap_or(0) | writes;
+
+        for successor in successors(pc, instruction) {
+            let Some(state) = defined_in.get_mut(successor) else {
+                continue;
+            };
+            let merged = state.map_or(defined_out, |defined| defined & defined_out);
+            if *state != Some(merged) {
+                *state = Some(merged);
+                worklist.push(successor);
+            }
+        }
+    }
+
+    let errors: Vec<VerifierError> = instructions
+        .iter()
+        .zip(&defined_in)
+        .enumerate()
+        .filter_map(|(pc, (instruction, defined))| defined.map(|defined| (pc, instruction, defined)))
+        .flat_map(|(pc, instruction, defined)| {
+            let (reads, _) = register_effects(instruction);
+            (0..11u8)
+                .filter(move |register| reads & !defined & (1 << register) != 0)
+                .map(move |register| VerifierError { instruction: pc, register })
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Registers read and written by an instruction, as bitmasks
+fn register_effects(instruction: &BpfInstruction) -> (u16, u16) {
+    use BpfOpcode::*;
+
+    let dst = 1u16 << instruction.dst_reg;
+    let src = 1u16 << instruction.src_reg;
+    let r0 = 1u16;
+
+    match instruction.opcode {
+        Mov64Imm | Mov32Imm | LdImm64 => (0, dst),
+        Mov64Reg | Mov32Reg => (src, dst),
+        Add32Imm | Sub32Imm | Mul32Imm | Div32Imm | Or32Imm | And32Imm | Lsh32Imm | Rsh32Imm
+        | Mod32Imm | Xor32Imm | Arsh32Imm | Neg32 => (dst, dst),
+        Add32Reg | Sub32Reg | Mul32Reg | Div32Reg | Or32Reg | And32Reg | Lsh32Reg | Rsh32Reg
+        | Mod32Reg | Xor32Reg | Arsh32Reg => (dst | src, dst),
+        Add64Imm | Sub64Imm | Mul64Imm | Div64Imm | Or64Imm | And64Imm | Lsh64Imm | Rsh64Imm
+        | Mod64Imm | Xor64Imm | Arsh64Imm | Neg64 => (dst, dst),
+        Add64Reg | Sub64Reg | Mul64Reg | Div64Reg | Or64Reg | And64Reg | Lsh64Reg | Rsh64Reg
+        | Mod64Reg | Xor64Reg | Arsh64Reg => (dst | src, dst),
+        LdAbs8 | LdAbs16 | LdAbs32 | LdAbs64 => (0, r0),
+        LdInd8 | LdInd16 | LdInd32 | LdInd64 => (src, r0),
+        Ldx8 | Ldx16 | Ldx32 | Ldx64 => (src, dst),
+        St8 | St16 | St32 | St64 => (dst, 0),
+        Stx8 | Stx16 | Stx32 | Stx64 => (dst | src, 0),
+        Ja => (0, 0),
+        JeqImm | JgtImm | JgeImm | JltImm | JleImm | JsetImm | JneImm | JsgtImm | JsgeImm
+        | JsltImm | JsleImm => (dst, 0),
+        JeqReg | JgtReg | JgeReg | JltReg | JleReg | JsetReg | JneReg | JsgtReg | JsgeReg
+        | JsltReg | JsleReg => (dst | src, 0),
+        Call => (0, r0),
+        Exit => (r0, 0),
+        Unknown(_) => (0, 0),
+    }
+}
+
+/// Instructions that may execute after `pc`
+pub(crate) fn successors(pc: usize, instruction: &BpfInstruction) -> Vec<usize> {
+    use BpfOpcode::*;
+
+    let next = pc + 1;
+    let target = (next as i64 + instruction.offset as i64).max(0) as usize;
+    match instruction.opcode {
+        Exit => vec![],
+        Ja => vec![target],
+        opcode if opcode.is_jump() => vec![next, target],
+        _ => vec![next],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpf_parser::BpfParser;
+
+    #[test]
+    fn test_clean_program_passes() {
+        // MOV64_REG R2, R1; JEQ_IMM R2, 0, +1; MOV64_IMM R3, 1; MOV64_IMM R0, 0; EXIT
+        let bytecode = vec![
+            0xbf, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x15, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xb7, 0x03, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+        assert_eq!(BpfParser::verify_register_init(&program), Ok(()));
+    }
+
+    #[test]
+    fn test_read_before_write_is_reported() {
+        // MOV64_IMM R0, 0; JEQ_IMM R1, 0, +1; MOV64_IMM R3, 1; ADD64_REG R0, R3; EXIT
+        let bytecode = vec![
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x15, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xb7, 0x03, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x0f, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let program = BpfParser::new().parse(&bytecode).unwrap();
+
+        let errors = BpfParser::verify_register_init(&program).unwrap_err();
+        assert_eq!(errors, vec![VerifierError { instruction: 3, register: 3 }]);
+        assert_eq!(errors[0].to_string(), "Register r3 read before initialization at instruction 3");
+    }
+
+    #[test]
+    fn test_checks_paths_from_the_entry_point() {
+        // MOV64_REG R0, R3; EXIT; MOV64_IMM R0, 0; EXIT
+        let bytecode = vec![
+            0xbf, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xb7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let mut program = BpfParser::new().parse(&bytecode).unwrap();
+        assert_eq!(
+            BpfParser::verify_register_init(&program),
+            Err(vec![VerifierError { instruction: 0, register: 3 }])
+        );
+
+        // Entering past the read-before-write prefix skips it
+        program.set_entry_offset(16).unwrap();
+        assert_eq!(BpfParser::verify_register_init(&program), Ok(()));
+    }
+}
@@ -0,0 +1,197 @@
+//! Explicit little-endian encoding shared by the input, witness and ELF formats
+//!
+//! Every multi-byte value crossing the host/guest boundary goes through these
+//! helpers so the byte order never depends on the host.
+
+use crate::error::TranspilerError;
+
+/// Read `N` bytes at `offset`
+pub fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Option<[u8; N]> {
+    let end = offset.checked_add(N)?;
+    bytes.get(offset..end).map(|slice| slice.try_into().unwrap())
+}
+
+/// Read a little-endian `u16` at `offset`
+pub fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u16> {
+    read_array(bytes, offset).map(u16::from_le_bytes)
+}
+
+/// Read a little-endian `u32` at `offset`
+pub fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    read_array(bytes, offset).map(u32::from_le_bytes)
+}
+
+/// Read a little-endian `u64` at `offset`
+pub fn read_u64_le(bytes: &[u8], offset: usize) -> Option<u64> {
+    read_array(bytes, offset).map(u64::from_le_bytes)
+}
+
+/// Append a little-endian `u16`
+pub fn write_u16_le(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Append a little-endian `u32`
+pub fn write_u32_le(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Append a little-endian `u64`
+pub fn write_u64_le(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Byte order of the `u32` words a 32-byte root is split into
+///
+/// Either way, word `i` holds root bytes `4*i..4*i + 4`; the order only
+/// decides which of them is the word's most significant byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WordOrder {
+    /// Byte `4*i` is the least significant (the ZisK output default)
+    #[default]
+    Little,
+    /// Byte `4*i` is the most significant, so printing the words as
+    /// `{:08x}` in order gives the hash's conventional hex form
+    Big,
+}
+
+/// Split a 32-byte root into the eight `u32` words written as ZisK outputs
+pub fn root_to_words(root: &[u8; 32]) -> [u32; 8] {
+    root_to_words_ordered(root, WordOrder::Little)
+}
+
+/// Reassemble a 32-byte root from eight ZisK output words
+pub fn root_from_words(words: &[u32; 8]) -> [u8; 32] {
+    root_from_words_ordered(words, WordOrder::Little)
+}
+
+/// Split a 32-byte root into eight `u32` words in the given byte order
+pub fn root_to_words_ordered(root: &[u8; 32], order: WordOrder) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(root.chunks_exact(4)) {
+        let chunk = chunk.try_into().unwrap();
+        *word = match order {
+            WordOrder::Little => u32::from_le_bytes(chunk),
+            WordOrder::Big => u32::from_be_bytes(chunk),
+        };
+    }
+    words
+}
+
+/// Reassemble a 32-byte root from words written with [`root_to_words_ordered`]
+pub fn root_from_words_ordered(words: &[u32; 8], order: WordOrder) -> [u8; 32] {
+    let mut root = [0u8; 32];
+    for (chunk, word) in root.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&match order {
+            WordOrder::Little => word.to_le_bytes(),
+            WordOrder::Big => word.to_be_bytes(),
+        });
+    }
+    root
+}
+
+/// Sequential reader reporting truncation through a format-specific error
+pub struct WireReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    error: fn(String) -> TranspilerError,
+}
+
+impl<'a> WireReader<'a> {
+    /// Read from the start of `bytes`, building errors with `error`
+    pub fn new(bytes: &'a [u8], error: fn(String) -> TranspilerError) -> Self {
+        Self { bytes, offset: 0, error }
+    }
+
+    /// Bytes consumed so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Bytes not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Consume `len` bytes
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], TranspilerError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| (self.error)(format!("truncated at offset {}", self.offset)))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    /// Consume a fixed-size array
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], TranspilerError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    /// Consume a little-endian `u16`
+    pub fn read_u16_le(&mut self) -> Result<u16, TranspilerError> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    /// Consume a little-endian `u32`
+    pub fn read_u32_le(&mut self) -> Result<u32, TranspilerError> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    /// Consume a little-endian `u64`
+    pub fn read_u64_le(&mut self) -> Result<u64, TranspilerError> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_word_chunking_round_trips() {
+        let root: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let words = root_to_words(&root);
+
+        assert_eq!(words[0], 0x0302_0100);
+        assert_eq!(words[7], 0x1f1e_1d1c);
+        assert_eq!(root_from_words(&words), root);
+    }
+
+    #[test]
+    fn test_ordered_words_reconstruct_sha256_digest() {
+        use sha2::{Digest, Sha256};
+
+        let digest: [u8; 32] = Sha256::digest(b"state").into();
+        for order in [WordOrder::Little, WordOrder::Big] {
+            assert_eq!(root_from_words_ordered(&root_to_words_ordered(&digest, order), order), digest);
+        }
+
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let big_words = root_to_words_ordered(&digest, WordOrder::Big);
+        let big: String = big_words.iter().map(|word| format!("{:08x}", word)).collect();
+        assert_eq!(big, hex);
+        let little_words = root_to_words_ordered(&digest, WordOrder::Little);
+        assert_eq!(little_words[0], u32::from_le_bytes(digest[..4].try_into().unwrap()));
+    }
+
+    #[test]
+    fn test_reader_and_writers_are_little_endian() {
+        let mut bytes = Vec::new();
+        write_u16_le(&mut bytes, 0x0102);
+        write_u32_le(&mut bytes, 0x0304_0506);
+        write_u64_le(&mut bytes, 0x0708_090a_0b0c_0d0e);
+        assert_eq!(&bytes[..6], &[0x02, 0x01, 0x06, 0x05, 0x04, 0x03]);
+        assert_eq!(read_u32_le(&bytes, 2), Some(0x0304_0506));
+        assert_eq!(read_u64_le(&bytes, 7), None);
+
+        let mut reader = WireReader::new(&bytes, |message| TranspilerError::Generic { message });
+        assert_eq!(reader.read_u16_le().unwrap(), 0x0102);
+        assert_eq!(reader.read_u32_le().unwrap(), 0x0304_0506);
+        assert_eq!(reader.read_u64_le().unwrap(), 0x0708_090a_0b0c_0d0e);
+        assert_eq!(reader.remaining(), 0);
+        assert!(reader.read_u16_le().unwrap_err().to_string().contains("truncated at offset 14"));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::merkle::{AccountLeaf, CommitmentHasher, MerkleTree, Sha256Hasher};
+use crate::wire::{write_u16_le, write_u32_le, write_u64_le, WireReader};
+
+/// Magic prefix identifying witness data
+pub const WITNESS_MAGIC: &[u8; 4] = b"ZKWT";
+/// Current witness format version
+pub const WITNESS_VERSION: u16 = 1;
+
+/// A single executed instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub pc: u64,
+    pub opcode: u8,
+    pub registers: [u64; 11],
+}
+
+/// Account state before and after execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChange {
+    pub pubkey: [u8; 32],
+    pub lamports_before: u64,
+    pub lamports_after: u64,
+    pub data_before: Vec<u8>,
+    pub data_after: Vec<u8>,
+}
+
+impl AccountChange {
+    /// Changed data bytes as `(offset, old_byte, new_byte)`
+    ///
+    /// If the data was resized, bytes past the end of the shorter side
+    /// compare as zero.
+    pub fn data_diff(&self) -> Vec<(usize, u8, u8)> {
+        let len = self.data_before.len().max(self.data_after.len());
+        (0..len)
+            .map(|offset| {
+                let old = self.data_before.get(offset).copied().unwrap_or(0);
+                let new = self.data_after.get(offset).copied().unwrap_or(0);
+                (offset, old, new)
+            })
+            .filter(|(_, old, new)| old != new)
+            .collect()
+    }
+
+    /// Signed change in lamports
+    pub fn lamports_delta(&self) -> i128 {
+        self.lamports_after as i128 - self.lamports_before as i128
+    }
+
+    /// Whether neither lamports nor data changed
+    pub fn is_noop(&self) -> bool {
+        self.lamports_before == self.lamports_after && self.data_before == self.data_after
+    }
+}
+
+/// Execution witness handed to the prover
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessData {
+    pub trace: Vec<TraceStep>,
+    pub account_changes: Vec<AccountChange>,
+}
+
+impl WitnessData {
+    /// Whether total lamports after execution plus `fee` equal the total before
+    ///
+    /// Solana rejects transactions that create or destroy lamports; a
+    /// mismatch here points at a buggy program or interpreter.
+    pub fn verify_lamports_conserved(&self, fee: u64) -> bool {
+        let before: u128 = self.account_changes.iter().map(|change| change.lamports_before as u128).sum();
+        let after: u128 = self.account_changes.iter().map(|change| change.lamports_after as u128).sum();
+        before == after + fee as u128
+    }
+}
+
+/// Serialize witness data
+///
+/// Account changes are written sorted by pubkey, so the output does not
+/// depend on the order they were recorded in.
+///
+/// Layout (all integers little-endian):
+/// `"ZKWT" | version: u16 | trace_len: u32 | [pc: u64, opcode: u8, r0..r10: u64]*
+/// | changes_len: u32 | [pubkey: 32, lamports_before: u64, lamports_after: u64,
+/// data_before_len: u32, data_before, data_after_len: u32, data_after]*`
+pub fn generate_witness_data(witness: &WitnessData) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(WITNESS_MAGIC);
+    write_u16_le(&mut bytes, WITNESS_VERSION);
+
+    write_u32_le(&mut bytes, witness.trace.len() as u32);
+    for step in &witness.trace {
+        write_u64_le(&mut bytes, step.pc);
+        bytes.push(step.opcode);
+        for register in step.registers {
+            write_u64_le(&mut bytes, register);
+        }
+    }
+
+    write_u32_le(&mut bytes, witness.account_changes.len() as u32);
+    for change in sorted_by_pubkey(&witness.account_changes) {
+        bytes.extend_from_slice(&change.pubkey);
+        write_u64_le(&mut bytes, change.lamports_before);
+        write_u64_le(&mut bytes, change.lamports_after);
+        write_u32_le(&mut bytes, change.data_before.len() as u32);
+        bytes.extend_from_slice(&change.data_before);
+        write_u32_le(&mut bytes, change.data_after.len() as u32);
+        bytes.extend_from_slice(&change.data_after);
+    }
+
+    bytes
+}
+
+/// Commitment to the post-execution state of the changed accounts
+///
+/// This is the Merkle root over `(pubkey, lamports_after, data_after)`
+/// leaves, which are sorted by pubkey, so it does not depend on the order
+/// in which accounts were touched. Fails if a pubkey appears twice.
+pub fn compute_account_state_hash(changes: &[AccountChange]) -> Result<[u8; 32], TranspilerError> {
+    compute_account_state_hash_with::<Sha256Hasher>(changes)
+}
+
+/// [`compute_account_state_hash`] with leaves and nodes hashed by `H`
+pub fn compute_account_state_hash_with<H: CommitmentHasher>(
+    changes: &[AccountChange],
+) -> Result<[u8; 32], TranspilerError> {
+    let leaves = changes
+        .iter()
+        .map(|change| AccountLeaf::new_with::<H>(change.pubkey, change.lamports_after, &change.data_after))
+        .collect();
+    Ok(MerkleTree::new_with::<H>(leaves)?.root())
+}
+
+fn sorted_by_pubkey(changes: &[AccountChange]) -> Vec<&AccountChange> {
+    let mut sorted: Vec<&AccountChange> = changes.iter().collect();
+    sorted.sort_by_key(|change| change.pubkey);
+    sorted
+}
+
+/// Parse witness data produced by [`generate_witness_data`]
+pub fn parse_witness_data(bytes: &[u8]) -> Result<WitnessData, TranspilerError> {
+    let mut reader = WireReader::new(bytes, invalid_witness);
+
+    if reader.take(WITNESS_MAGIC.len())? != WITNESS_MAGIC {
+        return Err(invalid_witness("missing ZKWT magic".to_string()));
+    }
+    let version = reader.read_u16_le()?;
+    if version != WITNESS_VERSION {
+        return Err(TranspilerError::ZiskExecutionError(
+            ZiskExecutionError::UnsupportedWitnessVersion { version },
+        ));
+    }
+
+    let trace_len = reader.read_u32_le()?;
+    let mut trace = Vec::new();
+    for _ in 0..trace_len {
+        let pc = reader.read_u64_le()?;
+        let opcode = reader.take(1)?[0];
+        let mut registers = [0u64; 11];
+        for register in &mut registers {
+            *register = reader.read_u64_le()?;
+        }
+        trace.push(TraceStep { pc, opcode, registers });
+    }
+
+    let changes_len = reader.read_u32_le()?;
+    let mut account_changes = Vec::new();
+    for _ in 0..changes_len {
+        let pubkey = reader.read_array()?;
+        let lamports_before = reader.read_u64_le()?;
+        let lamports_after = reader.read_u64_le()?;
+        let data_before_len = reader.read_u32_le()? as usize;
+        let data_before = reader.take(data_before_len)?.to_vec();
+        let data_after_len = reader.read_u32_le()? as usize;
+        let data_after = reader.take(data_after_len)?.to_vec();
+        account_changes.push(AccountChange {
+            pubkey,
+            lamports_before,
+            lamports_after,
+            data_before,
+            data_after,
+        });
+    }
+
+    if reader.remaining() != 0 {
+        return Err(invalid_witness(format!("{} trailing bytes", reader.remaining())));
+    }
+
+    Ok(WitnessData { trace, account_changes })
+}
+
+fn invalid_witness(message: String) -> TranspilerError {
+    TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidWitness { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_witness() -> WitnessData {
+        WitnessData {
+            trace: vec![
+                TraceStep { pc: 0, opcode: 0xb7, registers: [42, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x2_0004_0000] },
+                TraceStep { pc: 1, opcode: 0x95, registers: [42; 11] },
+            ],
+            account_changes: vec![AccountChange {
+                pubkey: [7; 32],
+                lamports_before: 1_000,
+                lamports_after: 900,
+                data_before: vec![],
+                data_after: vec![1, 2, 3],
+            }],
+        }
+    }
+
+    fn change(lamports_after: u64, data_after: Vec<u8>) -> AccountChange {
+        AccountChange {
+            pubkey: [1; 32],
+            lamports_before: 500,
+            lamports_after,
+            data_before: vec![1, 2, 3],
+            data_after,
+        }
+    }
+
+    #[test]
+    fn test_account_change_diffs() {
+        let lamports_only = change(200, vec![1, 2, 3]);
+        assert_eq!(lamports_only.lamports_delta(), -300);
+        assert!(lamports_only.data_diff().is_empty());
+        assert!(!lamports_only.is_noop());
+
+        let data_only = change(500, vec![1, 9, 3, 4]);
+        assert_eq!(data_only.lamports_delta(), 0);
+        assert_eq!(data_only.data_diff(), vec![(1, 2, 9), (3, 0, 4)]);
+        assert!(!data_only.is_noop());
+
+        let noop = change(500, vec![1, 2, 3]);
+        assert!(noop.is_noop());
+        assert!(noop.data_diff().is_empty());
+    }
+
+    #[test]
+    fn test_witness_round_trip() {
+        let witness = sample_witness();
+        let bytes = generate_witness_data(&witness);
+
+        assert_eq!(&bytes[..4], WITNESS_MAGIC);
+        assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), WITNESS_VERSION);
+        assert_eq!(parse_witness_data(&bytes).unwrap(), witness);
+    }
+
+    #[test]
+    fn test_lamports_conservation() {
+        let transfer = |to_lamports_after| WitnessData {
+            trace: Vec::new(),
+            account_changes: vec![
+                AccountChange { pubkey: [1; 32], ..change(300, vec![1, 2, 3]) },
+                AccountChange { pubkey: [2; 32], ..change(to_lamports_after, vec![1, 2, 3]) },
+            ],
+        };
+
+        assert!(transfer(700).verify_lamports_conserved(0));
+        assert!(transfer(695).verify_lamports_conserved(5));
+        assert!(!transfer(10_000).verify_lamports_conserved(0));
+        assert!(!transfer(700).verify_lamports_conserved(5));
+    }
+
+    #[test]
+    fn test_account_order_does_not_change_commitments() {
+        let changes: Vec<AccountChange> = (1..=4u8)
+            .map(|key| AccountChange { pubkey: [key; 32], ..change(100 * key as u64, vec![key]) })
+            .collect();
+        let mut shuffled = changes.clone();
+        shuffled.swap(0, 3);
+        shuffled.swap(1, 2);
+
+        let root = |changes: &[AccountChange]| compute_account_state_hash(changes).unwrap();
+        assert_eq!(root(&changes), root(&shuffled));
+        assert_ne!(root(&changes), root(&changes[1..]));
+        assert!(compute_account_state_hash(&[changes[0].clone(), changes[0].clone()]).is_err());
+
+        let witness = |account_changes| WitnessData { trace: Vec::new(), account_changes };
+        assert_eq!(generate_witness_data(&witness(changes)), generate_witness_data(&witness(shuffled)));
+    }
+
+    #[test]
+    fn test_witness_rejects_bad_header() {
+        let mut bytes = generate_witness_data(&sample_witness());
+        bytes[4] = 2;
+        let error = parse_witness_data(&bytes).unwrap_err();
+        assert!(error.to_string().contains("Unsupported witness version: 2"));
+
+        bytes[0] = b'X';
+        assert!(parse_witness_data(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_witness_rejects_truncated_data() {
+        let bytes = generate_witness_data(&sample_witness());
+        assert!(parse_witness_data(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_witness_truncated_at_every_boundary_is_an_error() {
+        let bytes = generate_witness_data(&sample_witness());
+        for len in 0..bytes.len() {
+            assert!(parse_witness_data(&bytes[..len]).is_err(), "accepted {} bytes", len);
+        }
+    }
+}
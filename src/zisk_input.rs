@@ -0,0 +1,236 @@
+use crate::bpf_interpreter::BpfInterpreter;
+use crate::error::{TranspilerError, ZiskExecutionError};
+use crate::program_registry::ProgramRegistry;
+use crate::wire::{read_u32_le, WireReader};
+use crate::ExecutionResult;
+use std::io::Read;
+use std::path::Path;
+
+/// Size of the little-endian program length prefix
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Input passed to the ZisK guest
+///
+/// Wire format (see `scripts/extract_bpf.sh`):
+/// `[program_size: u32 LE][program bytes][input data]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZiskInput {
+    pub program: Vec<u8>,
+    pub input_data: Vec<u8>,
+}
+
+/// Parse a framed ZisK input buffer
+pub fn parse_zisk_input_format(bytes: &[u8]) -> Result<ZiskInput, TranspilerError> {
+    let program_size = read_u32_le(bytes, 0).ok_or_else(|| {
+        invalid_input(format!("missing program size prefix ({} bytes)", bytes.len()))
+    })? as usize;
+    let program_end = LENGTH_PREFIX_SIZE
+        .checked_add(program_size)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| invalid_input(format!(
+            "program size {} exceeds available {} bytes",
+            program_size,
+            bytes.len() - LENGTH_PREFIX_SIZE
+        )))?;
+
+    Ok(ZiskInput {
+        program: bytes[LENGTH_PREFIX_SIZE..program_end].to_vec(),
+        input_data: bytes[program_end..].to_vec(),
+    })
+}
+
+/// Fixture input used when no real input is provided: `MOV64_IMM R0, 42; EXIT`
+pub fn create_test_zisk_input() -> ZiskInput {
+    ZiskInput {
+        program: vec![
+            0xb7, 0x00, 0x00, 0x00, 0x2a, 0x00, 0x00, 0x00, // MOV64_IMM R0, 42
+            0x95, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // EXIT
+        ],
+        input_data: Vec::new(),
+    }
+}
+
+/// Read a framed input, falling back to the test fixture when the input
+/// is empty or malformed
+pub fn read_zisk_input<R: Read>(reader: &mut R) -> Result<ZiskInput, TranspilerError> {
+    let mut input_bytes = Vec::new();
+    reader.read_to_end(&mut input_bytes)?;
+
+    if input_bytes.is_empty() {
+        return Ok(create_test_zisk_input());
+    }
+
+    Ok(parse_zisk_input_format(&input_bytes).unwrap_or_else(|_| create_test_zisk_input()))
+}
+
+/// Load a framed input from a file, e.g. one written by `scripts/extract_bpf.sh`
+///
+/// Unlike [`read_zisk_input`], malformed input is an error rather than
+/// falling back to the fixture.
+pub fn load_zisk_input_file(path: &Path) -> Result<ZiskInput, TranspilerError> {
+    let input_bytes = std::fs::read(path)?;
+    parse_zisk_input_format(&input_bytes)
+}
+
+/// An instruction of a [`MultiProgramInput`], dispatched to `program_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionInput {
+    pub program_id: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+/// Input carrying several programs and the instructions that invoke them
+///
+/// Wire format, all lengths and counts `u32` LE:
+/// `[program_count]([program_id: 32][len][bytecode])*`
+/// `[instruction_count]([program_id: 32][len][data])*`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiProgramInput {
+    pub programs: Vec<([u8; 32], Vec<u8>)>,
+    pub instructions: Vec<InstructionInput>,
+}
+
+impl MultiProgramInput {
+    /// Parse the programs into a registry keyed by program id
+    pub fn registry(&self) -> Result<ProgramRegistry, TranspilerError> {
+        let mut registry = ProgramRegistry::new();
+        for (program_id, bytecode) in &self.programs {
+            registry.load(*program_id, bytecode.clone())?;
+        }
+        Ok(registry)
+    }
+
+    /// Run each instruction against its program, with its data mapped as input
+    pub fn execute(&self) -> Result<Vec<ExecutionResult>, TranspilerError> {
+        let registry = self.registry()?;
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                let mut interpreter = BpfInterpreter::new();
+                interpreter.memory_mut().map_input(instruction.data.clone());
+                registry.execute_instruction(&instruction.program_id, &mut interpreter)
+            })
+            .collect()
+    }
+}
+
+/// Parse a [`MultiProgramInput`] buffer
+pub fn parse_multi_program_input(bytes: &[u8]) -> Result<MultiProgramInput, TranspilerError> {
+    let mut reader = WireReader::new(bytes, invalid_input);
+
+    let program_count = reader.read_u32_le()?;
+    let mut programs = Vec::new();
+    for _ in 0..program_count {
+        let program_id = reader.read_array()?;
+        let len = reader.read_u32_le()? as usize;
+        programs.push((program_id, reader.take(len)?.to_vec()));
+    }
+
+    let instruction_count = reader.read_u32_le()?;
+    let mut instructions = Vec::new();
+    for _ in 0..instruction_count {
+        let program_id = reader.read_array()?;
+        let len = reader.read_u32_le()? as usize;
+        instructions.push(InstructionInput { program_id, data: reader.take(len)?.to_vec() });
+    }
+
+    if reader.remaining() != 0 {
+        return Err(invalid_input(format!("{} trailing bytes", reader.remaining())));
+    }
+    Ok(MultiProgramInput { programs, instructions })
+}
+
+fn invalid_input(message: String) -> TranspilerError {
+    TranspilerError::ZiskExecutionError(ZiskExecutionError::InvalidInput { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_framed_input() {
+        let mut framed = 3u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(&[0xaa, 0xbb, 0xcc, 0x01, 0x02]);
+
+        let input = read_zisk_input(&mut framed.as_slice()).unwrap();
+        assert_eq!(input.program.len(), 3);
+        assert_eq!(input.input_data.len(), 2);
+        assert_eq!(input.program, vec![0xaa, 0xbb, 0xcc]);
+    }
+
+    #[test]
+    fn test_empty_input_yields_fixture() {
+        let input = read_zisk_input(&mut [].as_slice()).unwrap();
+        assert_eq!(input, create_test_zisk_input());
+    }
+
+    #[test]
+    fn test_truncated_input_is_rejected() {
+        let mut framed = 16u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(&[0x95; 8]);
+
+        assert!(parse_zisk_input_format(&framed).is_err());
+        assert_eq!(read_zisk_input(&mut framed.as_slice()).unwrap(), create_test_zisk_input());
+    }
+
+    #[test]
+    fn test_truncated_prefix_or_program_is_an_error() {
+        let mut framed = 4u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(&[1, 2, 3, 4]);
+
+        for len in 0..framed.len() {
+            assert!(parse_zisk_input_format(&framed[..len]).is_err(), "accepted {} bytes", len);
+        }
+        assert!(parse_zisk_input_format(&u32::MAX.to_le_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_multi_program_input_dispatches_by_id() {
+        // LDXB R0, [R1+0]; EXIT: returns the first byte of instruction data
+        let program = crate::builder::BpfProgramBuilder::new()
+            .instruction(crate::types::BpfOpcode::Ldx8, 0, 1, 0, 0)
+            .exit()
+            .build();
+        let mut framed = 1u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(&[5; 32]);
+        framed.extend_from_slice(&(program.len() as u32).to_le_bytes());
+        framed.extend_from_slice(&program);
+        framed.extend_from_slice(&1u32.to_le_bytes());
+        framed.extend_from_slice(&[5; 32]);
+        framed.extend_from_slice(&1u32.to_le_bytes());
+        framed.push(9);
+
+        let input = parse_multi_program_input(&framed).unwrap();
+        assert_eq!(input.programs, [([5; 32], program)]);
+        assert_eq!(input.instructions, [InstructionInput { program_id: [5; 32], data: vec![9] }]);
+        let results = input.execute().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].exit_code, 9);
+
+        for len in 0..framed.len() {
+            assert!(parse_multi_program_input(&framed[..len]).is_err(), "accepted {} bytes", len);
+        }
+        let end = framed.len();
+        framed[end - 37..end - 5].copy_from_slice(&[6; 32]);
+        assert!(parse_multi_program_input(&framed).unwrap().execute().is_err());
+    }
+
+    #[test]
+    fn test_input_file_runs_end_to_end() {
+        let path = std::env::temp_dir().join("zisk_input_end_to_end_test.bin");
+        let program = create_test_zisk_input().program;
+        let mut framed = (program.len() as u32).to_le_bytes().to_vec();
+        framed.extend_from_slice(&program);
+        std::fs::write(&path, &framed).unwrap();
+
+        let input = load_zisk_input_file(&path).unwrap();
+        let result = crate::BpfZiskExecutor::new().execute_native(&input.program).unwrap();
+        assert_eq!(result.exit_code, 42);
+
+        std::fs::write(&path, &framed[..6]).unwrap();
+        assert!(load_zisk_input_file(&path).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
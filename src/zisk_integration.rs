@@ -1,21 +1,67 @@
-use crate::error::{ZiskExecutionError, TranspilerError};
-use crate::types::BpfProgram;
+use crate::error::{BpfExitReason, ZiskExecutionError, TranspilerError};
+use crate::syscalls::describe_call;
+use crate::types::{BpfInstruction, BpfOpcode, BpfProgram};
 use crate::ExecutionResult;
 use std::process::Command;
 use std::fs;
 use std::path::Path;
 use std::time::Instant;
 
+/// Exit value returned by a metered guest that runs out of compute units
+///
+/// The guest exits with `r0 as i32`, so a program whose r0 truncates to this
+/// value is indistinguishable from one that ran out of budget.
+pub const GUEST_COMPUTE_EXCEEDED: i32 = -2;
+
+/// Exit value returned by a guest that divides or takes a remainder by zero
+///
+/// Like [`GUEST_COMPUTE_EXCEEDED`], it shares the `i32` exit value with r0.
+pub const GUEST_DIVISION_BY_ZERO: i32 = -3;
+
 pub struct ZiskIntegration {
     project_dir: String,
     target_dir: String,
+    compute_budget: Option<u64>,
 }
 
 impl ZiskIntegration {
     pub fn new() -> Self {
+        Self::with_project_dir("zisk_bpf_project")
+    }
+
+    /// Create an integration rooted at a custom project directory
+    pub fn with_project_dir(project_dir: &str) -> Self {
         Self {
-            project_dir: "zisk_bpf_project".to_string(),
+            project_dir: project_dir.to_string(),
             target_dir: "target/riscv64ima-zisk-zkvm-elf/release".to_string(),
+            compute_budget: None,
+        }
+    }
+
+    /// Meter the generated guest against `budget` compute units, or not at all with `None`
+    ///
+    /// The count lives in a guest-local `compute_units` counter rather than a
+    /// BPF register, so programs cannot clobber it. Every instruction but
+    /// `EXIT` costs one unit, as in the interpreter; a guest that exceeds the
+    /// budget returns [`GUEST_COMPUTE_EXCEEDED`].
+    pub fn set_compute_budget(&mut self, budget: Option<u64>) {
+        self.compute_budget = budget;
+    }
+
+    /// Get project layout and toolchain information
+    pub fn get_info(&self) -> ZiskInfo {
+        let zisk_version = Command::new("cargo-zisk")
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        ZiskInfo {
+            project_dir: self.project_dir.clone(),
+            target_dir: self.target_dir.clone(),
+            zisk_version,
         }
     }
 
@@ -53,6 +99,76 @@ rustflags = [
         Ok(())
     }
 
+    /// Whether the generated ZisK guest implements `opcode`; programs using others are rejected
+    pub fn supports_opcode(opcode: BpfOpcode) -> bool {
+        matches!(
+            opcode,
+            BpfOpcode::Mov64Imm
+                | BpfOpcode::Add64Imm
+                | BpfOpcode::Mov32Imm
+                | BpfOpcode::Mov32Reg
+                | BpfOpcode::Neg32
+                | BpfOpcode::Add32Imm
+                | BpfOpcode::Add32Reg
+                | BpfOpcode::Sub32Imm
+                | BpfOpcode::Sub32Reg
+                | BpfOpcode::Mul32Imm
+                | BpfOpcode::Mul32Reg
+                | BpfOpcode::Div32Imm
+                | BpfOpcode::Div32Reg
+                | BpfOpcode::Mod32Imm
+                | BpfOpcode::Mod32Reg
+                | BpfOpcode::Or32Imm
+                | BpfOpcode::Or32Reg
+                | BpfOpcode::And32Imm
+                | BpfOpcode::And32Reg
+                | BpfOpcode::Xor32Imm
+                | BpfOpcode::Xor32Reg
+                | BpfOpcode::Lsh32Imm
+                | BpfOpcode::Lsh32Reg
+                | BpfOpcode::Rsh32Imm
+                | BpfOpcode::Rsh32Reg
+                | BpfOpcode::Arsh32Imm
+                | BpfOpcode::Arsh32Reg
+                | BpfOpcode::Div64Imm
+                | BpfOpcode::Div64Reg
+                | BpfOpcode::Mod64Imm
+                | BpfOpcode::Mod64Reg
+                | BpfOpcode::Lsh64Reg
+                | BpfOpcode::Rsh64Reg
+                | BpfOpcode::Exit
+        )
+    }
+
+    /// Error for an instruction the guest generator does not lower
+    ///
+    /// `CALL` is split by `src_reg`, as the interpreter decodes it: 0 is a
+    /// syscall, which the guest has no host to serve, and anything else a
+    /// BPF-to-BPF call, which the guest has no call frames for.
+    fn unsupported_in_guest(index: usize, instruction: &BpfInstruction) -> TranspilerError {
+        let (instruction, reason) = match instruction.opcode {
+            BpfOpcode::Call => {
+                let description = describe_call(index, instruction.src_reg, instruction.immediate);
+                if instruction.src_reg == 0 {
+                    (description, "syscalls are not available in the guest")
+                } else {
+                    (description, "BPF-to-BPF calls are not supported in the guest")
+                }
+            }
+            opcode => (format!("{:?}", opcode), "opcode is not lowered"),
+        };
+        TranspilerError::ZiskExecutionError(ZiskExecutionError::UnsupportedInGuest { index, instruction, reason })
+    }
+
+    /// Guest expression for the low half of a 32-bit ALU operand
+    fn alu32_operand(instruction: &BpfInstruction) -> String {
+        if instruction.opcode.uses_src_register() {
+            format!("registers.get({}) as u32", instruction.src_reg)
+        } else {
+            format!("{}u32", instruction.immediate as u32)
+        }
+    }
+
     /// Generate Rust code for BPF interpreter in ZisK
     fn generate_interpreter_code(&self, bpf_program: &BpfProgram) -> Result<String, TranspilerError> {
         let mut code = String::new();
@@ -122,6 +238,14 @@ pub extern "C" fn main() -> i32 {
     // BPF program execution
     "#);
 
+        if let Some(budget) = self.compute_budget {
+            code.push_str(&format!("
+    // Compute unit meter, reserved for the metering preludes below
+    let mut compute_units: u64 = 0;
+    let compute_budget: u64 = {};
+    ", budget));
+        }
+
         // Add BPF program execution logic
         code.push_str(&format!("
     // Program has {} instructions
@@ -133,29 +257,145 @@ pub extern "C" fn main() -> i32 {
         // Generate instruction execution for each instruction
         for (i, instruction) in bpf_program.instructions.iter().enumerate() {
             code.push_str(&format!("\n        {} => {{", i));
+            if self.compute_budget.is_some() && instruction.opcode != BpfOpcode::Exit {
+                code.push_str(&format!(
+                    "compute_units += 1; if compute_units > compute_budget {{ return {}; }} ",
+                    GUEST_COMPUTE_EXCEEDED
+                ));
+            }
             
             match instruction.opcode {
                 crate::types::BpfOpcode::Mov64Imm => {
                     code.push_str(&format!(
-                        "registers.set({}, {});",
+                        "registers.set({}, {}i64 as u64);",
                         instruction.dst_reg, instruction.immediate
                     ));
                 }
                 crate::types::BpfOpcode::Add64Imm => {
                     code.push_str(&format!(
-                        "registers.set({}, registers.get({}) + {});",
+                        "registers.set({}, registers.get({}).wrapping_add({}i64 as u64));",
                         instruction.dst_reg, instruction.dst_reg, instruction.immediate
                     ));
                 }
-                crate::types::BpfOpcode::Exit => {
-                    code.push_str("return registers.r0 as i32;");
+                // 32-bit results are zero-extended, never sign-extended
+                crate::types::BpfOpcode::Mov32Imm => {
+                    code.push_str(&format!(
+                        "registers.set({}, {}u32 as u64);",
+                        instruction.dst_reg, instruction.immediate as u32
+                    ));
+                }
+                crate::types::BpfOpcode::Mov32Reg => {
+                    code.push_str(&format!(
+                        "registers.set({}, registers.get({}) as u32 as u64);",
+                        instruction.dst_reg, instruction.src_reg
+                    ));
+                }
+                crate::types::BpfOpcode::Neg32 => {
+                    code.push_str(&format!(
+                        "registers.set({}, (registers.get({}) as u32).wrapping_neg() as u64);",
+                        instruction.dst_reg, instruction.dst_reg
+                    ));
+                }
+                crate::types::BpfOpcode::Add32Imm
+                | crate::types::BpfOpcode::Add32Reg
+                | crate::types::BpfOpcode::Sub32Imm
+                | crate::types::BpfOpcode::Sub32Reg
+                | crate::types::BpfOpcode::Mul32Imm
+                | crate::types::BpfOpcode::Mul32Reg
+                | crate::types::BpfOpcode::Or32Imm
+                | crate::types::BpfOpcode::Or32Reg
+                | crate::types::BpfOpcode::And32Imm
+                | crate::types::BpfOpcode::And32Reg
+                | crate::types::BpfOpcode::Xor32Imm
+                | crate::types::BpfOpcode::Xor32Reg => {
+                    use BpfOpcode::*;
+
+                    let dst = format!("(registers.get({}) as u32)", instruction.dst_reg);
+                    let operand = Self::alu32_operand(instruction);
+                    let value = match instruction.opcode {
+                        Add32Imm | Add32Reg => format!("{}.wrapping_add({})", dst, operand),
+                        Sub32Imm | Sub32Reg => format!("{}.wrapping_sub({})", dst, operand),
+                        Mul32Imm | Mul32Reg => format!("{}.wrapping_mul({})", dst, operand),
+                        Or32Imm | Or32Reg => format!("({} | {})", dst, operand),
+                        And32Imm | And32Reg => format!("({} & {})", dst, operand),
+                        _ => format!("({} ^ {})", dst, operand),
+                    };
+                    code.push_str(&format!("registers.set({}, {} as u64);", instruction.dst_reg, value));
+                }
+                // A zero divisor stops the guest, as it faults the interpreter
+                crate::types::BpfOpcode::Div32Imm
+                | crate::types::BpfOpcode::Div32Reg
+                | crate::types::BpfOpcode::Mod32Imm
+                | crate::types::BpfOpcode::Mod32Reg => {
+                    let is_div = matches!(instruction.opcode, BpfOpcode::Div32Imm | BpfOpcode::Div32Reg);
+                    code.push_str(&format!(
+                        "let divisor = {}; if divisor == 0 {{ return {}; }} \
+                         registers.set({}, ((registers.get({}) as u32) {} divisor) as u64);",
+                        Self::alu32_operand(instruction),
+                        GUEST_DIVISION_BY_ZERO,
+                        instruction.dst_reg,
+                        instruction.dst_reg,
+                        if is_div { "/" } else { "%" }
+                    ));
+                }
+                // Unsigned, like the interpreter, so the guest gets RISC-V divu/remu
+                crate::types::BpfOpcode::Div64Imm
+                | crate::types::BpfOpcode::Div64Reg
+                | crate::types::BpfOpcode::Mod64Imm
+                | crate::types::BpfOpcode::Mod64Reg => {
+                    let divisor = if instruction.opcode.uses_src_register() {
+                        format!("registers.get({})", instruction.src_reg)
+                    } else {
+                        format!("{}i64 as u64", instruction.immediate)
+                    };
+                    let is_div = matches!(instruction.opcode, BpfOpcode::Div64Imm | BpfOpcode::Div64Reg);
+                    code.push_str(&format!(
+                        "let divisor: u64 = {}; if divisor == 0 {{ return {}; }} \
+                         registers.set({}, registers.get({}) {} divisor);",
+                        divisor,
+                        GUEST_DIVISION_BY_ZERO,
+                        instruction.dst_reg,
+                        instruction.dst_reg,
+                        if is_div { "/" } else { "%" }
+                    ));
+                }
+                // Shift amounts are masked to the operation's width, as BPF does
+                crate::types::BpfOpcode::Lsh32Reg | crate::types::BpfOpcode::Rsh32Reg => {
+                    let op = if instruction.opcode == BpfOpcode::Lsh32Reg { "<<" } else { ">>" };
+                    code.push_str(&format!(
+                        "registers.set({}, ((registers.get({}) as u32) {} (registers.get({}) & 31)) as u64);",
+                        instruction.dst_reg, instruction.dst_reg, op, instruction.src_reg
+                    ));
+                }
+                crate::types::BpfOpcode::Lsh32Imm | crate::types::BpfOpcode::Rsh32Imm => {
+                    let op = if instruction.opcode == BpfOpcode::Lsh32Imm { "<<" } else { ">>" };
+                    code.push_str(&format!(
+                        "registers.set({}, ((registers.get({}) as u32) {} {}) as u64);",
+                        instruction.dst_reg, instruction.dst_reg, op, instruction.immediate as u32 & 31
+                    ));
                 }
-                _ => {
+                crate::types::BpfOpcode::Arsh32Imm | crate::types::BpfOpcode::Arsh32Reg => {
+                    let shift = if instruction.opcode == BpfOpcode::Arsh32Imm {
+                        (instruction.immediate as u32 & 31).to_string()
+                    } else {
+                        format!("(registers.get({}) & 31)", instruction.src_reg)
+                    };
                     code.push_str(&format!(
-                        "// TODO: Implement {:?}",
-                        instruction.opcode
+                        "registers.set({}, ((registers.get({}) as u32 as i32) >> {}) as u32 as u64);",
+                        instruction.dst_reg, instruction.dst_reg, shift
                     ));
                 }
+                crate::types::BpfOpcode::Lsh64Reg | crate::types::BpfOpcode::Rsh64Reg => {
+                    let op = if instruction.opcode == BpfOpcode::Lsh64Reg { "<<" } else { ">>" };
+                    code.push_str(&format!(
+                        "registers.set({}, registers.get({}) {} (registers.get({}) & 63));",
+                        instruction.dst_reg, instruction.dst_reg, op, instruction.src_reg
+                    ));
+                }
+                crate::types::BpfOpcode::Exit => {
+                    code.push_str("return registers.r0 as i32;");
+                }
+                _ => return Err(Self::unsupported_in_guest(i, instruction)),
             }
             
             code.push_str("\n        }");
@@ -188,7 +428,7 @@ pub extern "C" fn main() -> i32 {
 
         // Build using cargo-zisk
         let output = Command::new("cargo-zisk")
-            .args(&["build", "--release"])
+            .args(["build", "--release"])
             .current_dir(&self.project_dir)
             .env("PATH", format!("{}:{}", std::env::var("PATH").unwrap_or_default(), "~/.zisk/bin"))
             .output()
@@ -225,7 +465,7 @@ pub extern "C" fn main() -> i32 {
         // Execute in ZisK emulator
         let start_time = Instant::now();
         let output = Command::new("ziskemu")
-            .args(&["-e", elf_name])
+            .args(["-e", elf_name])
             .current_dir(&self.project_dir)
             .env("PATH", format!("{}:{}", std::env::var("PATH").unwrap_or_default(), "~/.zisk/bin"))
             .output()
@@ -241,27 +481,46 @@ pub extern "C" fn main() -> i32 {
             }));
         }
 
-        // Parse exit code from output
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let exit_code = stdout.trim().parse::<u64>().unwrap_or(0);
+        let exit_reason = Self::exit_reason_from_output(&String::from_utf8_lossy(&output.stdout))?;
 
         Ok(ExecutionResult {
-            exit_code,
+            exit_code: exit_reason.code(),
+            exit_reason,
             registers: [0; 11], // TODO: Extract actual register values
             instructions_executed: bpf_program.instructions.len(),
             execution_time,
+            ..Default::default()
+        })
+    }
+
+    /// Exit reason for the value a guest printed when it returned
+    ///
+    /// [`GUEST_COMPUTE_EXCEEDED`] maps to `ComputeExceeded` and
+    /// [`GUEST_DIVISION_BY_ZERO`] to `DivByZero`, as the interpreter reports
+    /// them; any other value is r0 sign-extended from the guest's `i32` exit
+    /// value. An r0 that truncates to one of these sentinels is misreported.
+    fn exit_reason_from_output(stdout: &str) -> Result<BpfExitReason, TranspilerError> {
+        let value = stdout.trim().parse::<i32>().map_err(|_| {
+            TranspilerError::ZiskExecutionError(ZiskExecutionError::ExecutionError {
+                message: format!("Unexpected guest output: {:?}", stdout.trim()),
+            })
+        })?;
+        Ok(match value {
+            GUEST_COMPUTE_EXCEEDED => BpfExitReason::ComputeExceeded,
+            GUEST_DIVISION_BY_ZERO => BpfExitReason::DivByZero,
+            value => BpfExitReason::Ok(value as i64 as u64),
         })
     }
 
     /// Execute BPF program and generate proof in ZisK
     pub fn execute_with_proof(&self, bpf_program: &BpfProgram) -> Result<(ExecutionResult, Vec<u8>), TranspilerError> {
         // Build interpreter first
-        let elf_path = self.build_interpreter(bpf_program)?;
+        self.build_interpreter(bpf_program)?;
         let elf_name = "bpf_interpreter";
 
         // Generate ROM setup
         let rom_output = Command::new("cargo-zisk")
-            .args(&["rom-setup", "-e", elf_name])
+            .args(["rom-setup", "-e", elf_name])
             .current_dir(&self.project_dir)
             .env("PATH", format!("{}:{}", std::env::var("PATH").unwrap_or_default(), "~/.zisk/bin"))
             .output()
@@ -277,7 +536,7 @@ pub extern "C" fn main() -> i32 {
 
         // Generate proof
         let proof_output = Command::new("cargo-zisk")
-            .args(&["prove", "-e", elf_name, "-o", "proof", "-a", "-y"])
+            .args(["prove", "-e", elf_name, "-o", "proof", "-a", "-y"])
             .current_dir(&self.project_dir)
             .env("PATH", format!("{}:{}", std::env::var("PATH").unwrap_or_default(), "~/.zisk/bin"))
             .output()
@@ -331,12 +590,14 @@ mod tests {
 
     #[test]
     fn test_zisk_initialization() {
-        let mut zisk = ZiskIntegration::new();
+        let project_dir = std::env::temp_dir().join("zisk_bpf_project_init_test");
+        let mut zisk = ZiskIntegration::with_project_dir(project_dir.to_str().unwrap());
         let result = zisk.initialize();
         assert!(result.is_ok());
+        assert!(project_dir.join("Cargo.toml").exists());
         
         // Cleanup
-        let _ = fs::remove_dir_all("zisk_bpf_project");
+        let _ = fs::remove_dir_all(&project_dir);
     }
 
     #[test]
@@ -346,4 +607,156 @@ mod tests {
         assert_eq!(info.project_dir, "zisk_bpf_project");
         assert_eq!(info.target_dir, "target/riscv64ima-zisk-zkvm-elf/release");
     }
+
+    #[test]
+    fn test_mov32_and_neg32_are_zero_extended() {
+        use crate::builder::BpfProgramBuilder;
+
+        let bytecode = BpfProgramBuilder::new()
+            .instruction(BpfOpcode::Mov32Imm, 1, 0, 0, i32::MIN)
+            .instruction(BpfOpcode::Mov32Reg, 2, 1, 0, 0)
+            .instruction(BpfOpcode::Neg32, 2, 0, 0, 0)
+            .exit()
+            .build();
+        let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+        let code = ZiskIntegration::new().generate_interpreter_code(&program).unwrap();
+        assert!(!code.contains("TODO"));
+        assert!(code.contains("registers.set(1, 2147483648u32 as u64);"));
+        assert!(code.contains("registers.set(2, registers.get(1) as u32 as u64);"));
+        assert!(code.contains("registers.set(2, (registers.get(2) as u32).wrapping_neg() as u64);"));
+    }
+
+    #[test]
+    fn test_register_shifts_mask_to_their_width() {
+        use crate::builder::BpfProgramBuilder;
+
+        let bytecode = BpfProgramBuilder::new()
+            .mov64_imm(1, 3)
+            .mov64_imm(2, 40)
+            .instruction(BpfOpcode::Lsh32Reg, 1, 2, 0, 0)
+            .mov64_imm(3, 3)
+            .instruction(BpfOpcode::Lsh64Reg, 3, 2, 0, 0)
+            .exit()
+            .build();
+        let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+        let code = ZiskIntegration::new().generate_interpreter_code(&program).unwrap();
+        assert!(!code.contains("TODO"));
+        assert!(code.contains("registers.set(1, ((registers.get(1) as u32) << (registers.get(2) & 31)) as u64);"));
+        assert!(code.contains("registers.set(3, registers.get(3) << (registers.get(2) & 63));"));
+    }
+
+    #[test]
+    fn test_exit_reason_from_guest_output() {
+        let exit_reason = |stdout| ZiskIntegration::exit_reason_from_output(stdout).ok();
+        assert_eq!(exit_reason("42\n"), Some(BpfExitReason::Ok(42)));
+        assert_eq!(exit_reason("-5"), Some(BpfExitReason::Ok(-5i64 as u64)));
+        assert_eq!(exit_reason("-2\n"), Some(BpfExitReason::ComputeExceeded));
+        assert_eq!(exit_reason("-3"), Some(BpfExitReason::DivByZero));
+        assert_eq!(exit_reason("-3").unwrap().code(), crate::error::ARITHMETIC_OVERFLOW);
+        assert_eq!(exit_reason(""), None);
+        assert_eq!(exit_reason("panicked"), None);
+    }
+
+    #[test]
+    fn test_alu32_lowerings_are_zero_extended() {
+        use crate::builder::BpfProgramBuilder;
+
+        let bytecode = BpfProgramBuilder::new()
+            .instruction(BpfOpcode::Add32Imm, 1, 0, 0, -1)
+            .instruction(BpfOpcode::Xor32Reg, 1, 2, 0, 0)
+            .instruction(BpfOpcode::Mod32Imm, 1, 0, 0, 7)
+            .instruction(BpfOpcode::Rsh32Imm, 1, 0, 0, 33)
+            .instruction(BpfOpcode::Arsh32Reg, 1, 2, 0, 0)
+            .exit()
+            .build();
+        let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+        assert!(program.instructions.iter().all(|i| ZiskIntegration::supports_opcode(i.opcode)));
+
+        let code = ZiskIntegration::new().generate_interpreter_code(&program).unwrap();
+        assert!(!code.contains("TODO"));
+        assert!(code.contains("registers.set(1, (registers.get(1) as u32).wrapping_add(4294967295u32) as u64);"));
+        assert!(code.contains("registers.set(1, ((registers.get(1) as u32) ^ registers.get(2) as u32) as u64);"));
+        assert!(code.contains(
+            "let divisor = 7u32; if divisor == 0 { return -3; } \
+             registers.set(1, ((registers.get(1) as u32) % divisor) as u64);"
+        ));
+        assert!(code.contains("registers.set(1, ((registers.get(1) as u32) >> 1) as u64);"));
+        assert!(code.contains(
+            "registers.set(1, ((registers.get(1) as u32 as i32) >> (registers.get(2) & 31)) as u32 as u64);"
+        ));
+    }
+
+    #[test]
+    fn test_div64_and_mod64_are_unsigned_and_guarded() {
+        use crate::builder::BpfProgramBuilder;
+
+        let bytecode = BpfProgramBuilder::new()
+            .mov64_imm(1, -2)
+            .instruction(BpfOpcode::Div64Imm, 1, 0, 0, 2)
+            .instruction(BpfOpcode::Mod64Reg, 1, 2, 0, 0)
+            .exit()
+            .build();
+        let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+        let code = ZiskIntegration::new().generate_interpreter_code(&program).unwrap();
+        assert!(!code.contains("TODO"));
+        assert!(code.contains(
+            "let divisor: u64 = 2i64 as u64; if divisor == 0 { return -3; } \
+             registers.set(1, registers.get(1) / divisor);"
+        ));
+        assert!(code.contains(
+            "let divisor: u64 = registers.get(2); if divisor == 0 { return -3; } \
+             registers.set(1, registers.get(1) % divisor);"
+        ));
+    }
+
+    #[test]
+    fn test_compute_budget_emits_metering_preludes() {
+        use crate::builder::BpfProgramBuilder;
+
+        let bytecode = BpfProgramBuilder::new().mov64_imm(0, 1).add64_imm(0, 2).exit().build();
+        let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+        let prelude = "compute_units += 1; if compute_units > compute_budget { return -2; }";
+
+        let mut zisk = ZiskIntegration::new();
+        zisk.set_compute_budget(Some(1));
+        let code = zisk.generate_interpreter_code(&program).unwrap();
+        assert!(code.contains("let compute_budget: u64 = 1;"));
+        assert_eq!(code.matches(prelude).count(), 2);
+        assert!(code.contains("2 => {return registers.r0 as i32;"));
+
+        zisk.set_compute_budget(None);
+        let code = zisk.generate_interpreter_code(&program).unwrap();
+        assert!(!code.contains("compute_units"));
+    }
+
+    #[test]
+    fn test_unsupported_opcodes_are_rejected() {
+        use crate::builder::BpfProgramBuilder;
+
+        for code in 0..=u8::MAX {
+            let Some(opcode) = BpfOpcode::from_code(code) else {
+                continue;
+            };
+            let bytecode = BpfProgramBuilder::new().instruction(opcode, 1, 0, 0, 1).exit().build();
+            let program = crate::BpfParser::new().parse(&bytecode).unwrap();
+            let generated = ZiskIntegration::new().generate_interpreter_code(&program);
+            assert_eq!(generated.is_ok(), ZiskIntegration::supports_opcode(opcode), "{:?}", opcode);
+        }
+
+        let syscall = BpfProgramBuilder::new().syscall(crate::syscalls::SOL_LOG).exit().build();
+        let error = ZiskIntegration::new()
+            .generate_interpreter_code(&crate::BpfParser::new().parse(&syscall).unwrap())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "ZisK execution error: at BPF instruction #0: the ZisK guest cannot run `call sol_log_`: \
+             syscalls are not available in the guest"
+        );
+
+        let local = BpfProgramBuilder::new().instruction(BpfOpcode::Call, 0, 1, 0, 2).exit().build();
+        let error = ZiskIntegration::new()
+            .generate_interpreter_code(&crate::BpfParser::new().parse(&local).unwrap())
+            .unwrap_err();
+        assert!(error.to_string().contains("`call function_0x18 (+2)`: BPF-to-BPF calls are not supported"));
+    }
 }
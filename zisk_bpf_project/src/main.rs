@@ -66,7 +66,7 @@ pub extern "C" fn main() -> i32 {
     
     while pc < program_size {
         match pc {
-        0 => {registers.set(0, 42);
+        0 => {registers.set(0, 42i64 as u64);
         }
         1 => {return registers.r0 as i32;
         }